@@ -0,0 +1,110 @@
+use chip8::CHIP8;
+use chip8::capture::Recorder;
+use chip8::cli::RunArgs;
+use chip8::emulator::Rng;
+use chip8::emulator::errors::EmuError;
+use chip8::frontend::Movie;
+use chip8::runner::{KeyScript, Outcome, Runner, StopCondition};
+use clap::Parser;
+use std::process::ExitCode;
+
+/* Exit status when the run ends on an error or the final frame differs from --expect-hash or the movie */
+const EXIT_FAILURE: u8 = 1;
+/* Exit status when stop conditions were given but none was met within the frame limit */
+const EXIT_TIMEOUT: u8 = 2;
+
+fn main() -> Result<ExitCode, EmuError> {
+    let args = RunArgs::parse();
+
+    let mut emulator = CHIP8::with_platform(args.platform);
+    if let Some(speed) = args.speed {
+        emulator.set_cycles_per_frame(speed);
+    }
+    emulator.set_timer_hz(args.timer_hz);
+    emulator.set_error_policy(args.on_error);
+    emulator.set_rng(Rng::with_algorithm(args.rng, args.seed));
+    /* Nothing rewinds here, so skip the per-frame snapshots */
+    emulator.set_rewind_frames(0);
+    emulator.load(&args.rom)?;
+    let movie = match &args.play {
+        Some(path) => {
+            let movie = Movie::load(path)?;
+            movie.prepare(&mut emulator)?;
+            Some(movie)
+        }
+        None => None,
+    };
+
+    if args.capture.is_some() || args.capture_audio.is_some() {
+        let recorder = Recorder::start(
+            args.capture.as_deref(),
+            args.capture_audio.as_deref(),
+            args.capture_scale,
+        )?;
+        emulator.start_capture(recorder)?;
+    }
+
+    let mut script = match &args.keys {
+        Some(path) => KeyScript::load(path)?,
+        None => KeyScript::new(),
+    };
+    for &press in &args.press {
+        script.push(press);
+    }
+
+    let frames = movie.as_ref().map_or(args.frames, Movie::len);
+    let expected_hash = args
+        .expect_hash
+        .or(movie.as_ref().and_then(|movie| movie.final_hash));
+    let mut runner = Runner::new(emulator, frames);
+    runner.set_script(script);
+    if let Some(movie) = movie {
+        runner.set_movie(movie);
+    }
+    for &addr in &args.until_pc {
+        runner.add_condition(StopCondition::Pc(addr));
+    }
+    for &hash in &args.until_hash {
+        runner.add_condition(StopCondition::FrameHash(hash));
+    }
+    if args.until_loop {
+        runner.add_condition(StopCondition::SelfLoop);
+    }
+
+    let outcome = runner.run();
+    if let Some(recorder) = runner.chip8_mut().stop_capture() {
+        let path = recorder.video_path().map(|path| path.display().to_string());
+        match (recorder.finish()?, path) {
+            (frames, Some(path)) => eprintln!("Captured {frames} frames to {path}"),
+            (frames, None) => eprintln!("Captured {frames} frames of audio"),
+        }
+    }
+    let mut report = runner.report(&outcome);
+    let mismatch = expected_hash.filter(|&hash| hash != report.frame_hash);
+    /* The report agrees with the exit status */
+    report.success &= mismatch.is_none();
+    if args.json {
+        println!("{:#}", report.to_json());
+    } else {
+        print!("{report}");
+    }
+    if let Outcome::Fault(error) = &outcome {
+        eprintln!("{}", runner.chip8().crash_report(error));
+    }
+    if let Some(path) = &args.png {
+        report.screen.scaled(args.png_scale).save_png(path)?;
+    }
+
+    if let Some(expected) = mismatch {
+        eprintln!(
+            "Final frame hash {:016x} differs from the expected {expected:016x}",
+            report.frame_hash
+        );
+    }
+    let status = match outcome {
+        _ if report.success => 0,
+        Outcome::FrameLimit if mismatch.is_none() => EXIT_TIMEOUT,
+        _ => EXIT_FAILURE,
+    };
+    Ok(ExitCode::from(status))
+}
@@ -0,0 +1,60 @@
+use chip8::cli::SuiteArgs;
+use chip8::emulator::errors::EmuError;
+use chip8::runner::{SuiteReport, SuiteTest};
+use clap::Parser;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+fn main() -> Result<ExitCode, EmuError> {
+    let args = SuiteArgs::parse();
+
+    let mut roms: Vec<(SuiteTest, PathBuf)> = Vec::new();
+    for path in &args.paths {
+        let files = match path.is_dir() {
+            true => {
+                let mut files = std::fs::read_dir(path)?
+                    .map(|entry| entry.map(|entry| entry.path()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                files.sort();
+                files
+            }
+            false => vec![path.clone()],
+        };
+        for file in files {
+            match SuiteTest::identify(&file) {
+                Some(test) => roms.push((test, file)),
+                /* Directories may hold the suite's other ROMs, which have no results to read */
+                None if path.is_dir() => {}
+                None => eprintln!("Skipping {}: not a known test ROM", file.display()),
+            }
+        }
+    }
+    if roms.is_empty() {
+        eprintln!(
+            "No test ROMs found, expected names containing corax, flags, quirks, keypad or beep"
+        );
+        return Ok(ExitCode::FAILURE);
+    }
+
+    let mut reports: Vec<SuiteReport> = Vec::new();
+    for (test, rom) in roms {
+        let menu = match test {
+            SuiteTest::Quirks => args.quirks_menu,
+            _ => None,
+        };
+        reports.push(test.run(&rom, args.platform, menu)?);
+    }
+    if args.json {
+        let reports: Vec<Value> = reports.iter().map(SuiteReport::to_json).collect();
+        println!("{:#}", Value::Array(reports));
+    } else {
+        for report in &reports {
+            print!("{report}");
+        }
+    }
+    Ok(match reports.iter().all(SuiteReport::passed) {
+        true => ExitCode::SUCCESS,
+        false => ExitCode::FAILURE,
+    })
+}
@@ -1,17 +1,183 @@
 use chip8::CHIP8;
-use chip8::cli::RomSelector;
+use chip8::capture::Recorder;
+use chip8::cli::args::DEFAULT_HEADLESS_FRAMES;
+use chip8::cli::{Args, RomSelector};
+use chip8::debugger::{DapServer, Debugger, GdbServer, Repl};
 use chip8::emulator::errors::EmuError;
+use chip8::emulator::{Rng, TraceFilter, TraceSink, Tracer};
+use chip8::frontend::{
+    Frontend, MinifbFrontend, Movie, NullFrontend, PlaybackFrontend, RecordingFrontend,
+};
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
 
 fn main() -> Result<(), EmuError> {
-    let mut rom = RomSelector::new();
-    match rom.select() {
-        Some(rom) => {
-            let mut emulator = CHIP8::new();
-            emulator.load(rom)?;
-            emulator.run()?;
+    let args = Args::parse();
+    /* Without a ROM, the DAP client names one in its launch request */
+    if args.dap && args.rom.is_none() {
+        let input = std::io::BufReader::new(std::io::stdin());
+        DapServer::new().serve(input, std::io::stdout(), &mut MinifbFrontend::new())?;
+        return Ok(());
+    }
+    let rom = match args.rom.clone() {
+        Some(rom) => rom.into_os_string(),
+        None => match RomSelector::new().select() {
+            Some(rom) => rom.clone(),
+            None => return Ok(()),
+        },
+    };
+
+    let mut emulator = CHIP8::with_platform(args.platform);
+    if let Some(speed) = args.speed {
+        emulator.set_cycles_per_frame(speed);
+    }
+    emulator.set_timer_hz(args.timer_hz);
+    emulator.set_error_policy(args.on_error);
+    let seed = args.seed.unwrap_or_else(rand::random);
+    emulator.set_rng(Rng::with_algorithm(args.rng, seed));
+    let Some(rewind_frames) = args.rewind.checked_mul(chip8::FRAME_RATE as usize) else {
+        Args::command()
+            .error(ErrorKind::ValueValidation, "--rewind is too long")
+            .exit();
+    };
+    emulator.set_rewind_frames(rewind_frames);
+    emulator.set_screenshot_scale(args.screenshot_scale);
+    emulator.load(rom)?;
+    if let Some(path) = &args.trace {
+        let sink = if path.as_os_str() == "-" {
+            TraceSink::Stderr
+        } else {
+            TraceSink::file(path)?
+        };
+        let filter = TraceFilter {
+            range: args.trace_range.clone(),
+            classes: args.trace_class.clone(),
+        };
+        emulator.set_tracer(Some(Tracer::with_filter(sink, filter)));
+    }
+    if args.debug {
+        let mut repl = Repl::new(Debugger::new(emulator));
+        repl.run(std::io::stdin().lock(), std::io::stdout())?;
+        return Ok(());
+    }
+    if args.dap {
+        let input = std::io::BufReader::new(std::io::stdin());
+        let mut server = DapServer::with_debugger(Debugger::new(emulator));
+        server.serve(input, std::io::stdout(), &mut MinifbFrontend::new())?;
+        return Ok(());
+    }
+    if let Some(port) = args.gdb {
+        eprintln!("Waiting for GDB on 127.0.0.1:{port}");
+        let mut server = GdbServer::new(Debugger::new(emulator));
+        server.listen(("127.0.0.1", port), &mut MinifbFrontend::new())?;
+        return Ok(());
+    }
+    if args.capture.is_some() || args.capture_audio.is_some() {
+        let recorder = Recorder::start(
+            args.capture.as_deref(),
+            args.capture_audio.as_deref(),
+            args.capture_scale,
+        )?;
+        emulator.start_capture(recorder)?;
+    }
+    let result = run_session(&mut emulator, &args, seed);
+    /* Also finishes clips started with the hotkey */
+    emulator.finish_capture();
+    result
+}
+
+/* Runs the ROM in a window, or headless, playing back or recording a movie if asked to */
+fn run_session(emulator: &mut CHIP8, args: &Args, seed: u64) -> Result<(), EmuError> {
+    let movie = match &args.play {
+        Some(path) => {
+            let movie = Movie::load(path)?;
+            movie.prepare(emulator)?;
+            Some(movie)
+        }
+        None => None,
+    };
+    if args.headless {
+        return run_headless(emulator, movie, args);
+    }
+    if let Some(movie) = movie {
+        let mut player = PlaybackFrontend::new(MinifbFrontend::new(), movie);
+        return run(emulator, &mut player, args);
+    }
+    if let Some(path) = &args.record {
+        let movie = Movie::record(emulator, seed);
+        let mut recorder = RecordingFrontend::new(MinifbFrontend::new(), movie);
+        let result = run(emulator, &mut recorder, args);
+        let movie = recorder.movie();
+        match movie.save(path) {
+            Ok(()) => eprintln!("Recorded {} frames to {}", movie.len(), path.display()),
+            Err(e) => eprintln!("Could not write movie to {}: {e}", path.display()),
+        }
+        return result;
+    }
+    run(emulator, &mut MinifbFrontend::new(), args)
+}
+
+/* Runs until the window closes, on an error prints the crash report and keeps the last frame on screen */
+fn run<F: Frontend>(emulator: &mut CHIP8, frontend: &mut F, args: &Args) -> Result<(), EmuError> {
+    if let Err(error) = emulator.run(frontend) {
+        report_crash(emulator, &error, args);
+        /* Keep the frozen frame on screen until the window is closed */
+        emulator.hold_frame(frontend);
+        return Err(error);
+    }
+    Ok(())
+}
+
+fn report_crash(emulator: &CHIP8, error: &EmuError, args: &Args) {
+    let report = emulator.crash_report(error);
+    eprintln!("{report}");
+    if let Some(path) = &args.crash_report {
+        match report.save(path) {
+            Ok(()) => eprintln!("Crash report written to {}", path.display()),
+            Err(e) => eprintln!("Could not write crash report to {}: {e}", path.display()),
+        }
+    }
+}
+
+/*
+    Runs without a window, a movie to its end or --frames frames, then prints the final
+    frame hash, checking it against the recording when playing a movie
+*/
+fn run_headless(emulator: &mut CHIP8, movie: Option<Movie>, args: &Args) -> Result<(), EmuError> {
+    let mut frontend = NullFrontend::new();
+    frontend.max_frames = args.frames;
+    let Some(movie) = movie else {
+        frontend.max_frames = Some(args.frames.unwrap_or(DEFAULT_HEADLESS_FRAMES));
+        if let Err(error) = emulator.run(&mut frontend) {
+            report_crash(emulator, &error, args);
+            return Err(error);
+        }
+        let hash = emulator.display().frame_hash();
+        println!(
+            "Ran {} frames, final frame hash {hash:016x}",
+            frontend.frames
+        );
+        return Ok(());
+    };
+    let mut player = PlaybackFrontend::until_end(frontend, movie);
+    if let Err(error) = emulator.run(&mut player) {
+        report_crash(emulator, &error, args);
+        return Err(error);
+    }
+    let hash = player
+        .last_hash()
+        .unwrap_or_else(|| emulator.display().frame_hash());
+    println!(
+        "Played {} frames, final frame hash {hash:016x}",
+        player.frame()
+    );
+    match player.movie().final_hash {
+        Some(expected) if expected != hash => {
+            eprintln!("Final frame differs from the recording ({expected:016x})");
+            std::process::exit(1);
         }
+        Some(_) => println!("Final frame matches the recording"),
         None => {}
     }
-    
     Ok(())
 }
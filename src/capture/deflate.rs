@@ -0,0 +1,191 @@
+/*
+    Minimal zlib (RFC 1950/1951) compressor for PNG output: a single deflate
+    block with the fixed Huffman codes and greedy LZ77 matching. CHIP-8 frames
+    are mostly long runs, which this compresses well without any dependency.
+*/
+
+const WINDOW_SIZE: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+/* Candidates tried per position, trading ratio for speed */
+const MAX_CHAIN: usize = 64;
+const HASH_BITS: u32 = 15;
+const END_OF_BLOCK: u16 = 256;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/* zlib stream of `data` */
+pub fn zlib_compress(data: &[u8]) -> Vec<u8> {
+    /* 32K window deflate, no preset dictionary, header checksum making 0x7801 a multiple of 31 */
+    let mut out = BitWriter::new(vec![0x78, 0x01]);
+    deflate(data, &mut out);
+    let mut bytes = out.finish();
+    bytes.extend_from_slice(&adler32(data).to_be_bytes());
+    bytes
+}
+
+fn deflate(data: &[u8], out: &mut BitWriter) {
+    /* BFINAL = 1, BTYPE = 01 (fixed Huffman codes) */
+    out.bits(0b011, 3);
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut prev = vec![usize::MAX; data.len()];
+    let mut pos = 0;
+    while pos < data.len() {
+        let (length, distance) = longest_match(data, pos, &head, &prev);
+        let end = if length >= MIN_MATCH {
+            write_match(out, length, distance);
+            pos + length
+        } else {
+            write_literal(out, data[pos] as u16);
+            pos + 1
+        };
+        while pos < end {
+            if pos + MIN_MATCH <= data.len() {
+                let hash = hash(&data[pos..]);
+                prev[pos] = head[hash];
+                head[hash] = pos;
+            }
+            pos += 1;
+        }
+    }
+    write_literal(out, END_OF_BLOCK);
+}
+
+#[inline]
+fn hash(bytes: &[u8]) -> usize {
+    let value = u32::from(bytes[0]) << 16 | u32::from(bytes[1]) << 8 | u32::from(bytes[2]);
+    (value.wrapping_mul(0x9E37_79B1) >> (32 - HASH_BITS)) as usize
+}
+
+/* Longest earlier occurrence of the bytes at pos, as (length, distance) */
+fn longest_match(data: &[u8], pos: usize, head: &[usize], prev: &[usize]) -> (usize, usize) {
+    if pos + MIN_MATCH > data.len() {
+        return (0, 0);
+    }
+    let max = (data.len() - pos).min(MAX_MATCH);
+    let mut best = (0, 0);
+    let mut candidate = head[hash(&data[pos..])];
+    for _ in 0..MAX_CHAIN {
+        if candidate == usize::MAX || pos - candidate > WINDOW_SIZE {
+            break;
+        }
+        let length = data[candidate..]
+            .iter()
+            .zip(&data[pos..pos + max])
+            .take_while(|(a, b)| a == b)
+            .count();
+        if length > best.0 {
+            best = (length, pos - candidate);
+            if length == max {
+                break;
+            }
+        }
+        candidate = prev[candidate];
+    }
+    best
+}
+
+/* Fixed Huffman code of a literal/length symbol, RFC 1951 3.2.6 */
+fn write_literal(out: &mut BitWriter, symbol: u16) {
+    let (code, len) = match symbol {
+        0..=143 => (0x30 + symbol, 8),
+        144..=255 => (0x190 + symbol - 144, 9),
+        256..=279 => (symbol - 256, 7),
+        _ => (0xC0 + symbol - 280, 8),
+    };
+    out.huffman(code, len);
+}
+
+fn write_match(out: &mut BitWriter, length: usize, distance: usize) {
+    let index = LENGTH_BASE
+        .iter()
+        .rposition(|&base| base as usize <= length)
+        .unwrap_or(0);
+    write_literal(out, 257 + index as u16);
+    out.bits(
+        (length - LENGTH_BASE[index] as usize) as u32,
+        LENGTH_EXTRA[index],
+    );
+    let index = DISTANCE_BASE
+        .iter()
+        .rposition(|&base| base as usize <= distance)
+        .unwrap_or(0);
+    out.huffman(index as u16, 5);
+    out.bits(
+        (distance - DISTANCE_BASE[index] as usize) as u32,
+        DISTANCE_EXTRA[index],
+    );
+}
+
+/* Deflate bit stream, filled from the least significant bit */
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    count: u8,
+}
+impl BitWriter {
+    fn new(bytes: Vec<u8>) -> BitWriter {
+        BitWriter {
+            bytes,
+            buffer: 0,
+            count: 0,
+        }
+    }
+    fn bits(&mut self, value: u32, count: u8) {
+        self.buffer |= value << self.count;
+        self.count += count;
+        while self.count >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+    /* Huffman codes are stored starting from their most significant bit */
+    fn huffman(&mut self, code: u16, len: u8) {
+        let reversed = code.reverse_bits() >> (16 - len);
+        self.bits(reversed as u32, len);
+    }
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (a, b) = data
+        .chunks(5552)
+        .fold((1u32, 0u32), |(mut a, mut b), chunk| {
+            for &byte in chunk {
+                a += byte as u32;
+                b += a;
+            }
+            (a % MOD, b % MOD)
+        });
+    b << 16 | a
+}
+
+/* CRC-32 (ISO 3309) as used by PNG chunks and gzip */
+pub fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &byte| {
+        (0..8).fold(crc ^ byte as u32, |crc, _| {
+            (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg())
+        })
+    })
+}
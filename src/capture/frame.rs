@@ -0,0 +1,70 @@
+use crate::capture::png;
+use crate::emulator::display::{Display, Palette};
+use std::io;
+use std::path::Path;
+
+/* Largest integer scale accepted for exported frames */
+pub const MAX_SCALE: usize = 32;
+/* Characters drawn for palette indices 0-3 by Frame::to_ascii */
+pub const ASCII_PIXELS: [char; 4] = ['.', '#', '+', '*'];
+
+/* Screen contents as palette indices, at the pixel buffer's resolution or scaled up */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub width: usize,
+    pub height: usize,
+    pub palette: Palette,
+    /* Palette index of every pixel, row by row */
+    pub pixels: Vec<u8>,
+}
+impl Frame {
+    /* Copies the display at the size of its pixel buffer, with the active palette */
+    pub fn capture(display: &Display) -> Frame {
+        let (width, height) = (display.buffer_width(), display.buffer_height());
+        Frame {
+            width,
+            height,
+            palette: display.palette(),
+            pixels: (0..width * height).map(|i| display.get_planes(i)).collect(),
+        }
+    }
+    /* Frame with every pixel repeated into a scale x scale block, scale is clamped to 1..=MAX_SCALE */
+    pub fn scaled(&self, scale: usize) -> Frame {
+        let scale = scale.clamp(1, MAX_SCALE);
+        let width = self.width * scale;
+        let mut pixels = Vec::with_capacity(width * self.height * scale);
+        for row in self.pixels.chunks(self.width.max(1)) {
+            let start = pixels.len();
+            pixels.extend(row.iter().flat_map(|&p| std::iter::repeat_n(p, scale)));
+            for _ in 1..scale {
+                pixels.extend_from_within(start..start + width);
+            }
+        }
+        Frame {
+            width,
+            height: self.height * scale,
+            palette: self.palette,
+            pixels,
+        }
+    }
+    #[inline]
+    /* Colour of a pixel as 0x00RRGGBB */
+    pub fn rgb(&self, index: usize) -> u32 {
+        self.palette[self.pixels[index] as usize]
+    }
+    /* One line of text per row, see ASCII_PIXELS */
+    pub fn to_ascii(&self) -> String {
+        let mut text = String::with_capacity((self.width + 1) * self.height);
+        for row in self.pixels.chunks(self.width.max(1)) {
+            text.extend(row.iter().map(|&p| ASCII_PIXELS[p as usize & 3]));
+            text.push('\n');
+        }
+        text
+    }
+    pub fn to_png(&self) -> Vec<u8> {
+        png::encode_indexed(self.width, self.height, &self.palette, &self.pixels)
+    }
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        std::fs::write(path, self.to_png())
+    }
+}
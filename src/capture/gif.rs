@@ -0,0 +1,170 @@
+use crate::FRAME_RATE;
+use crate::capture::Frame;
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/* Largest LZW code, GIF codes are at most 12 bits */
+const MAX_CODES: u16 = 4096;
+const MAX_CODE_SIZE: u8 = 12;
+/* Bits per pixel of the colour table, GIF needs at least 2 for LZW */
+const COLOR_BITS: u8 = 2;
+const CENTISECONDS: u64 = 100;
+
+/*
+    Animated GIF writer. A frame identical to the previous one only extends
+    how long the previous one is shown, so static screens cost nothing.
+    Delays are rounded on the running time, so 60 frames per second alternate
+    between 1 and 2 hundredths of a second without drifting.
+*/
+#[derive(Debug)]
+pub struct GifEncoder<W: Write> {
+    out: W,
+    width: usize,
+    height: usize,
+    /* Frame waiting for its delay to be known, and how many frames it lasts */
+    pending: Option<(Frame, u64)>,
+    /* Frames and hundredths of a second written so far */
+    frames: u64,
+    centiseconds: u64,
+}
+impl<W: Write> GifEncoder<W> {
+    /* Writes the header and colour table, all frames must have the size and palette of `first` */
+    pub fn new(mut out: W, first: &Frame) -> io::Result<GifEncoder<W>> {
+        out.write_all(b"GIF89a")?;
+        out.write_all(&(first.width as u16).to_le_bytes())?;
+        out.write_all(&(first.height as u16).to_le_bytes())?;
+        /* Global colour table, colour resolution, table size 2^(COLOR_BITS) */
+        out.write_all(&[0x80 | (COLOR_BITS - 1) << 4 | (COLOR_BITS - 1), 0, 0])?;
+        for &rgb in &first.palette {
+            out.write_all(&[(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8])?;
+        }
+        /* Loop forever */
+        out.write_all(&[0x21, 0xFF, 0x0B])?;
+        out.write_all(b"NETSCAPE2.0")?;
+        out.write_all(&[0x03, 0x01, 0x00, 0x00, 0x00])?;
+        Ok(GifEncoder {
+            out,
+            width: first.width,
+            height: first.height,
+            pending: None,
+            frames: 0,
+            centiseconds: 0,
+        })
+    }
+    pub fn push(&mut self, frame: &Frame) -> io::Result<()> {
+        if frame.width != self.width || frame.height != self.height {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "frame size changed during GIF capture",
+            ));
+        }
+        match &mut self.pending {
+            Some((pending, count)) if pending.pixels == frame.pixels => *count += 1,
+            _ => {
+                self.write_pending()?;
+                self.pending = Some((frame.clone(), 1));
+            }
+        }
+        Ok(())
+    }
+    /* Writes the last frame and the trailer */
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_pending()?;
+        self.out.write_all(&[0x3B])?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    fn write_pending(&mut self) -> io::Result<()> {
+        let Some((frame, count)) = self.pending.take() else {
+            return Ok(());
+        };
+        self.frames += count;
+        let end = (self.frames * CENTISECONDS + FRAME_RATE as u64 / 2) / FRAME_RATE as u64;
+        let delay = end
+            .saturating_sub(self.centiseconds)
+            .clamp(1, u16::MAX as u64);
+        self.centiseconds += delay;
+        /* Graphic control extension: no disposal, no transparency, delay */
+        self.out.write_all(&[0x21, 0xF9, 0x04, 0x00])?;
+        self.out.write_all(&(delay as u16).to_le_bytes())?;
+        self.out.write_all(&[0x00, 0x00])?;
+        /* Image descriptor covering the whole screen, using the global colour table */
+        self.out.write_all(&[0x2C, 0, 0, 0, 0])?;
+        self.out.write_all(&(self.width as u16).to_le_bytes())?;
+        self.out.write_all(&(self.height as u16).to_le_bytes())?;
+        self.out.write_all(&[0x00, COLOR_BITS])?;
+        for block in lzw_encode(COLOR_BITS, &frame.pixels).chunks(255) {
+            self.out.write_all(&[block.len() as u8])?;
+            self.out.write_all(block)?;
+        }
+        self.out.write_all(&[0x00])
+    }
+}
+
+/* GIF flavoured LZW: variable code size from min_code_size + 1 up to 12 bits, LSB first */
+fn lzw_encode(min_code_size: u8, pixels: &[u8]) -> Vec<u8> {
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+    let mut out = CodeWriter::default();
+    let mut code_size = min_code_size + 1;
+    let mut next = end + 1;
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    out.write(clear, code_size);
+    let Some((&first, rest)) = pixels.split_first() else {
+        out.write(end, code_size);
+        return out.finish();
+    };
+    let mut prefix = first as u16;
+    for &pixel in rest {
+        if let Some(&code) = table.get(&(prefix, pixel)) {
+            prefix = code;
+            continue;
+        }
+        out.write(prefix, code_size);
+        /* The decoder grows its code size one entry behind the encoder */
+        if next >= 1 << code_size && code_size < MAX_CODE_SIZE {
+            code_size += 1;
+        }
+        if next < MAX_CODES {
+            table.insert((prefix, pixel), next);
+            next += 1;
+        } else {
+            out.write(clear, code_size);
+            table.clear();
+            next = end + 1;
+            code_size = min_code_size + 1;
+        }
+        prefix = pixel as u16;
+    }
+    out.write(prefix, code_size);
+    if next >= 1 << code_size && code_size < MAX_CODE_SIZE {
+        code_size += 1;
+    }
+    out.write(end, code_size);
+    out.finish()
+}
+
+#[derive(Default)]
+struct CodeWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    count: u8,
+}
+impl CodeWriter {
+    fn write(&mut self, code: u16, size: u8) {
+        self.buffer |= (code as u32) << self.count;
+        self.count += size;
+        while self.count >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
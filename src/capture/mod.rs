@@ -0,0 +1,14 @@
+pub mod deflate;
+pub mod frame;
+pub mod gif;
+pub mod png;
+pub mod recorder;
+pub mod screenshot;
+pub mod video;
+pub mod wav;
+
+pub use frame::Frame;
+pub use recorder::Recorder;
+pub use screenshot::{DEFAULT_SCREENSHOT_SCALE, capture_path, save_screenshot};
+pub use video::VideoFormat;
+pub use wav::Tone;
@@ -0,0 +1,43 @@
+use crate::capture::deflate::{crc32, zlib_compress};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+const BIT_DEPTH: u8 = 8;
+const COLOR_TYPE_INDEXED: u8 = 3;
+/* Row filter type written before every scanline */
+const FILTER_NONE: u8 = 0;
+
+/* Encodes an 8-bit indexed colour PNG, `pixels` holds one palette index per pixel, row by row */
+pub fn encode_indexed(width: usize, height: usize, palette: &[u32], pixels: &[u8]) -> Vec<u8> {
+    let mut png = SIGNATURE.to_vec();
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    /* Bit depth, colour type, deflate compression, adaptive filtering, no interlace */
+    header.extend_from_slice(&[BIT_DEPTH, COLOR_TYPE_INDEXED, 0, 0, 0]);
+    write_chunk(&mut png, b"IHDR", &header);
+
+    let colours: Vec<u8> = palette
+        .iter()
+        .flat_map(|&rgb| [(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8])
+        .collect();
+    write_chunk(&mut png, b"PLTE", &colours);
+
+    let mut scanlines = Vec::with_capacity((width + 1) * height);
+    for row in pixels.chunks(width.max(1)).take(height) {
+        scanlines.push(FILTER_NONE);
+        scanlines.extend_from_slice(row);
+    }
+    write_chunk(&mut png, b"IDAT", &zlib_compress(&scanlines));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
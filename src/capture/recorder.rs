@@ -0,0 +1,90 @@
+use crate::capture::Frame;
+use crate::capture::video::{VideoEncoder, VideoFormat};
+use crate::capture::wav::{Tone, WavWriter};
+use crate::emulator::display::Display;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+
+/* Session recording presented frames to a video file and the beeper to a WAV file */
+#[derive(Debug)]
+pub struct Recorder {
+    video: Option<(PathBuf, VideoFormat)>,
+    /* Created on the first frame, once the frame size is known */
+    encoder: Option<VideoEncoder>,
+    audio: Option<WavWriter<BufWriter<File>>>,
+    scale: usize,
+    frames: usize,
+}
+impl Recorder {
+    /*
+        Starts recording to `video` (.gif, .y4m, .rgb or .raw) and/or `audio` (.wav),
+        frames are scaled by `scale`
+    */
+    pub fn start(video: Option<&Path>, audio: Option<&Path>, scale: usize) -> io::Result<Recorder> {
+        let video = match video {
+            Some(path) => {
+                let format = VideoFormat::from_path(path).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!(
+                            "unknown video format for {}, expected .gif, .y4m, .rgb or .raw",
+                            path.display()
+                        ),
+                    )
+                })?;
+                Some((path.to_path_buf(), format))
+            }
+            None => None,
+        };
+        let audio = match audio {
+            Some(path) => Some(WavWriter::new(BufWriter::new(File::create(path)?))?),
+            None => None,
+        };
+        Ok(Recorder {
+            video,
+            encoder: None,
+            audio,
+            scale,
+            frames: 0,
+        })
+    }
+    /* Appends the display and the tone played during the frame */
+    pub fn frame(&mut self, display: &Display, tone: Tone) -> io::Result<()> {
+        if let Some((path, format)) = &self.video {
+            let frame = Frame::capture(display).scaled(self.scale);
+            match &mut self.encoder {
+                Some(encoder) => encoder.push(&frame)?,
+                None => {
+                    let mut encoder = VideoEncoder::create(path, *format, &frame)?;
+                    encoder.push(&frame)?;
+                    self.encoder = Some(encoder);
+                }
+            }
+        }
+        if let Some(audio) = &mut self.audio {
+            audio.push(tone)?;
+        }
+        self.frames += 1;
+        Ok(())
+    }
+    #[inline]
+    /* Frames recorded so far */
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+    #[inline]
+    pub fn video_path(&self) -> Option<&Path> {
+        self.video.as_ref().map(|(path, _)| path.as_path())
+    }
+    /* Completes the files, returns the number of frames recorded */
+    pub fn finish(self) -> io::Result<usize> {
+        if let Some(encoder) = self.encoder {
+            encoder.finish()?;
+        }
+        if let Some(audio) = self.audio {
+            audio.finish()?;
+        }
+        Ok(self.frames)
+    }
+}
@@ -0,0 +1,59 @@
+use crate::capture::Frame;
+use crate::emulator::display::Display;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const DEFAULT_SCREENSHOT_SCALE: usize = 1;
+
+/* Saves the display as a PNG in `dir`, named after the ROM and the current time, returns its path */
+pub fn save_screenshot(
+    display: &Display,
+    scale: usize,
+    dir: &Path,
+    rom: Option<&Path>,
+) -> io::Result<PathBuf> {
+    let path = capture_path(dir, rom, SystemTime::now(), "png");
+    Frame::capture(display).scaled(scale).save_png(&path)?;
+    Ok(path)
+}
+
+/* File in `dir` named <rom>-<YYYYMMDD-HHMMSS>.<extension>, numbered if that name is taken */
+pub fn capture_path(dir: &Path, rom: Option<&Path>, time: SystemTime, extension: &str) -> PathBuf {
+    let stem = rom
+        .and_then(Path::file_stem)
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "chip8".to_string());
+    let name = format!("{stem}-{}", timestamp(time));
+    let mut path = dir.join(format!("{name}.{extension}"));
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("{name}-{n}.{extension}"));
+        n += 1;
+    }
+    path
+}
+
+/* UTC time as YYYYMMDD-HHMMSS */
+fn timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let (days, secs) = (secs / 86400, secs % 86400);
+    /* Civil date from days since 1970-01-01, after Howard Hinnant's days_from_civil inverse */
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    format!(
+        "{year:04}{month:02}{day:02}-{:02}{:02}{:02}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
@@ -0,0 +1,96 @@
+use crate::FRAME_RATE;
+use crate::capture::Frame;
+use crate::capture::gif::GifEncoder;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/* Video formats a capture can be written in, picked from the file extension */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoFormat {
+    /* Animated GIF, repeated frames merged */
+    Gif,
+    /* Uncompressed YUV4MPEG2 (4:4:4), understood by ffmpeg and most encoders */
+    Y4m,
+    /* Headerless 24-bit RGB frames, e.g. ffmpeg -f rawvideo -pix_fmt rgb24 -s WxH -r 60 */
+    Rgb,
+}
+impl VideoFormat {
+    pub fn from_path(path: &Path) -> Option<VideoFormat> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "gif" => Some(VideoFormat::Gif),
+            "y4m" => Some(VideoFormat::Y4m),
+            "rgb" | "raw" => Some(VideoFormat::Rgb),
+            _ => None,
+        }
+    }
+}
+
+/* Writes frames to a video file, the size is taken from the first frame */
+#[derive(Debug)]
+pub enum VideoEncoder {
+    Gif(GifEncoder<BufWriter<File>>),
+    Y4m(BufWriter<File>),
+    Rgb(BufWriter<File>),
+}
+impl VideoEncoder {
+    pub fn create(path: &Path, format: VideoFormat, first: &Frame) -> io::Result<VideoEncoder> {
+        let mut out = BufWriter::new(File::create(path)?);
+        Ok(match format {
+            VideoFormat::Gif => VideoEncoder::Gif(GifEncoder::new(out, first)?),
+            VideoFormat::Y4m => {
+                writeln!(
+                    out,
+                    "YUV4MPEG2 W{} H{} F{FRAME_RATE}:1 Ip A1:1 C444",
+                    first.width, first.height
+                )?;
+                VideoEncoder::Y4m(out)
+            }
+            VideoFormat::Rgb => VideoEncoder::Rgb(out),
+        })
+    }
+    pub fn push(&mut self, frame: &Frame) -> io::Result<()> {
+        match self {
+            VideoEncoder::Gif(gif) => gif.push(frame),
+            VideoEncoder::Y4m(out) => {
+                out.write_all(b"FRAME\n")?;
+                let pixels: Vec<[u8; 3]> = (0..frame.pixels.len())
+                    .map(|i| rgb_to_yuv(frame.rgb(i)))
+                    .collect();
+                /* Planar: all Y, then all U, then all V */
+                for plane in 0..3 {
+                    let bytes: Vec<u8> = pixels.iter().map(|yuv| yuv[plane]).collect();
+                    out.write_all(&bytes)?;
+                }
+                Ok(())
+            }
+            VideoEncoder::Rgb(out) => {
+                let bytes: Vec<u8> = (0..frame.pixels.len())
+                    .map(|i| frame.rgb(i))
+                    .flat_map(|rgb| [(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8])
+                    .collect();
+                out.write_all(&bytes)
+            }
+        }
+    }
+    pub fn finish(self) -> io::Result<()> {
+        match self {
+            VideoEncoder::Gif(gif) => gif.finish().map(|_| ()),
+            VideoEncoder::Y4m(mut out) | VideoEncoder::Rgb(mut out) => out.flush(),
+        }
+    }
+}
+
+/* BT.601 limited range Y'CbCr */
+fn rgb_to_yuv(rgb: u32) -> [u8; 3] {
+    let (r, g, b) = (
+        (rgb >> 16 & 0xFF) as i32,
+        (rgb >> 8 & 0xFF) as i32,
+        (rgb & 0xFF) as i32,
+    );
+    let y = ((66 * r + 129 * g + 25 * b + 128) >> 8) + 16;
+    let u = ((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128;
+    let v = ((112 * r - 94 * g - 18 * b + 128) >> 8) + 128;
+    [y as u8, u as u8, v as u8]
+}
@@ -0,0 +1,94 @@
+use crate::FRAME_RATE;
+use crate::emulator::cpu::AUDIO_PATTERN_BYTES;
+use std::io::{self, Seek, SeekFrom, Write};
+
+pub const SAMPLE_RATE: u32 = 48000;
+const SAMPLES_PER_FRAME: u32 = SAMPLE_RATE / FRAME_RATE;
+/* Beep frequency, as played by the window frontend */
+const BEEP_HZ: f32 = 440.0;
+const VOLUME: f32 = 0.25;
+/* Size of the RIFF header before the samples */
+const HEADER_LEN: u32 = 44;
+
+/* What the beeper plays during a frame */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tone {
+    Silent,
+    /* Default CHIP-8 beep */
+    Beep,
+    /* XO-CHIP audio pattern and pitch */
+    Pattern([u8; AUDIO_PATTERN_BYTES], u8),
+}
+
+/* 16-bit mono PCM WAV writer, the header sizes are filled in by finish */
+#[derive(Debug)]
+pub struct WavWriter<W: Write + Seek> {
+    out: W,
+    samples: u32,
+    /* Position in the current waveform, in cycles for the beep and pattern bits for patterns */
+    phase: f32,
+}
+impl<W: Write + Seek> WavWriter<W> {
+    pub fn new(mut out: W) -> io::Result<WavWriter<W>> {
+        write_header(&mut out, 0)?;
+        Ok(WavWriter {
+            out,
+            samples: 0,
+            phase: 0.0,
+        })
+    }
+    /* Appends one frame worth of samples */
+    pub fn push(&mut self, tone: Tone) -> io::Result<()> {
+        let mut bytes = Vec::with_capacity(SAMPLES_PER_FRAME as usize * 2);
+        for _ in 0..SAMPLES_PER_FRAME {
+            let sample = match tone {
+                Tone::Silent => {
+                    self.phase = 0.0;
+                    0.0
+                }
+                Tone::Beep => {
+                    let sample = (self.phase * std::f32::consts::TAU).sin() * VOLUME;
+                    self.phase = (self.phase + BEEP_HZ / SAMPLE_RATE as f32).fract();
+                    sample
+                }
+                Tone::Pattern(pattern, pitch) => {
+                    /* XO-CHIP playback rate: 4000 * 2^((pitch - 64) / 48) bits per second */
+                    let rate = 4000.0 * 2f32.powf((f32::from(pitch) - 64.0) / 48.0);
+                    let bits = (AUDIO_PATTERN_BYTES * 8) as f32;
+                    let bit = self.phase as usize % (AUDIO_PATTERN_BYTES * 8);
+                    self.phase = (self.phase + rate / SAMPLE_RATE as f32) % bits;
+                    match pattern[bit / 8] & (0x80 >> (bit % 8)) {
+                        0 => -VOLUME,
+                        _ => VOLUME,
+                    }
+                }
+            };
+            bytes.extend_from_slice(&((sample * i16::MAX as f32) as i16).to_le_bytes());
+        }
+        self.samples += SAMPLES_PER_FRAME;
+        self.out.write_all(&bytes)
+    }
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.seek(SeekFrom::Start(0))?;
+        write_header(&mut self.out, self.samples * 2)?;
+        self.out.seek(SeekFrom::End(0))?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+fn write_header<W: Write>(out: &mut W, data_len: u32) -> io::Result<()> {
+    out.write_all(b"RIFF")?;
+    out.write_all(&(HEADER_LEN - 8 + data_len).to_le_bytes())?;
+    out.write_all(b"WAVEfmt ")?;
+    /* PCM, mono, 16 bits per sample */
+    out.write_all(&16u32.to_le_bytes())?;
+    out.write_all(&1u16.to_le_bytes())?;
+    out.write_all(&1u16.to_le_bytes())?;
+    out.write_all(&SAMPLE_RATE.to_le_bytes())?;
+    out.write_all(&(SAMPLE_RATE * 2).to_le_bytes())?;
+    out.write_all(&2u16.to_le_bytes())?;
+    out.write_all(&16u16.to_le_bytes())?;
+    out.write_all(b"data")?;
+    out.write_all(&data_len.to_le_bytes())
+}
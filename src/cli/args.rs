@@ -0,0 +1,129 @@
+use crate::capture::DEFAULT_SCREENSHOT_SCALE;
+use crate::capture::frame::MAX_SCALE;
+use crate::debugger::symbols::parse_address;
+use crate::emulator::{ErrorPolicy, OpcodeClass, Platform, RngAlgorithm};
+use crate::{
+    DEFAULT_REWIND_SECONDS, DEFAULT_TIMER_HZ, MAX_REWIND_SECONDS, MAX_TIMER_HZ, MIN_TIMER_HZ,
+};
+use clap::{ArgGroup, Parser};
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+/* Frames run headless without a movie, ten seconds of gameplay */
+pub const DEFAULT_HEADLESS_FRAMES: usize = 600;
+
+/* Command line arguments of the chip8 binary */
+#[derive(Debug, Parser)]
+#[command(name = "chip8", version, about = "CHIP-8 Interpreter")]
+#[command(group(ArgGroup::new("headless_source").args(["play", "capture", "capture_audio"]).multiple(true)))]
+pub struct Args {
+    /// ROM to run, prompts for one of the bundled ROMs if omitted
+    pub rom: Option<PathBuf>,
+
+    /// Platform profile: chip8, vip, chip48, schip10, schip11 or xochip
+    #[arg(long, default_value_t = Platform::Chip8)]
+    pub platform: Platform,
+
+    /// Instructions executed per frame, adjustable at runtime with - and =
+    /// [default: the platform's speed]
+    #[arg(long)]
+    pub speed: Option<usize>,
+
+    /// On bad memory accesses, stack errors and unknown opcodes: halt, wrap or ignore
+    #[arg(long, default_value_t = ErrorPolicy::Halt)]
+    pub on_error: ErrorPolicy,
+
+    /// Delay and sound timer frequency in Hz (1-1000)
+    #[arg(
+        long,
+        default_value_t = DEFAULT_TIMER_HZ,
+        value_parser = clap::value_parser!(u32).range(MIN_TIMER_HZ as i64..=MAX_TIMER_HZ as i64)
+    )]
+    pub timer_hz: u32,
+
+    /// Seed for the CXNN random number generator, the same seed replays the same run
+    /// [default: random]
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// CXNN random number generator: xorshift, or vip-like to approximate the COSMAC VIP
+    #[arg(long, default_value_t = RngAlgorithm::Xorshift)]
+    pub rng: RngAlgorithm,
+
+    /// Seconds of gameplay kept for rewinding with Backspace (0-600), 0 disables rewinding
+    #[arg(long, value_name = "SECONDS", default_value_t = DEFAULT_REWIND_SECONDS, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(0..=MAX_REWIND_SECONDS as u64))]
+    pub rewind: usize,
+
+    /// Integer scale of screenshots saved with F12 (1-32)
+    #[arg(long, value_name = "SCALE", default_value_t = DEFAULT_SCREENSHOT_SCALE, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..=MAX_SCALE as u64))]
+    pub screenshot_scale: usize,
+
+    /// Also write the crash report to FILE when emulation halts on an error
+    #[arg(long, value_name = "FILE")]
+    pub crash_report: Option<PathBuf>,
+
+    /// Start paused in the terminal debugger instead of opening a window
+    #[arg(long)]
+    pub debug: bool,
+
+    /// Wait for GDB on 127.0.0.1:PORT and run the ROM under its control
+    #[arg(long, value_name = "PORT", conflicts_with = "debug")]
+    pub gdb: Option<u16>,
+
+    /// Serve the Debug Adapter Protocol on stdin/stdout, the ROM comes from the launch request
+    #[arg(long, conflicts_with_all = ["debug", "gdb"])]
+    pub dap: bool,
+
+    /// Record the keypad state of every frame and the RNG seed to a movie FILE
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["debug", "gdb", "dap"])]
+    pub record: Option<PathBuf>,
+
+    /// Play back a movie FILE, then hand over to the keyboard
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["debug", "gdb", "dap", "record"])]
+    pub play: Option<PathBuf>,
+
+    /// Record the screen to FILE: an animated .gif, or uncompressed .y4m or raw .rgb video
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["debug", "gdb", "dap"])]
+    pub capture: Option<PathBuf>,
+
+    /// Record the beeper to a .wav FILE
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["debug", "gdb", "dap"])]
+    pub capture_audio: Option<PathBuf>,
+
+    /// Integer scale of the frames written by --capture (1-32)
+    #[arg(long, value_name = "SCALE", default_value_t = DEFAULT_SCREENSHOT_SCALE, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..=MAX_SCALE as u64))]
+    pub capture_scale: usize,
+
+    /// With --play or --capture, run without a window and print the final frame hash
+    #[arg(long, requires = "headless_source")]
+    pub headless: bool,
+
+    /// Frames run by --headless, stopping earlier at the end of a movie
+    /// [default: 600 without --play]
+    #[arg(long, value_name = "N", requires = "headless")]
+    pub frames: Option<usize>,
+
+    /// Trace executed instructions to FILE, or to stderr if no file is given
+    #[arg(long, value_name = "FILE", num_args = 0..=1, default_missing_value = "-")]
+    pub trace: Option<PathBuf>,
+
+    /// Only trace instructions fetched from START-END, e.g. 0x200-0x2ff
+    #[arg(long, value_name = "START-END", value_parser = parse_address_range)]
+    pub trace_range: Option<RangeInclusive<u16>>,
+
+    /// Only trace these opcode classes: flow, alu, memory, display, input, timer, machine
+    #[arg(long, value_name = "CLASS", value_delimiter = ',')]
+    pub trace_class: Vec<OpcodeClass>,
+}
+
+/* Parses an inclusive START-END address range */
+pub fn parse_address_range(s: &str) -> Result<RangeInclusive<u16>, String> {
+    let (start, end) = s
+        .split_once('-')
+        .ok_or_else(|| format!("expected START-END, got '{s}'"))?;
+    let (start, end) = (parse_address(start)?, parse_address(end)?);
+    if start > end {
+        return Err(format!("range start {start:#06x} is after end {end:#06x}"));
+    }
+    Ok(start..=end)
+}
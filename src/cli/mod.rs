@@ -1,2 +1,8 @@
+pub mod args;
 pub mod rom_selector;
+pub mod run_args;
+pub mod suite_args;
+pub use args::Args;
 pub use rom_selector::RomSelector;
+pub use run_args::RunArgs;
+pub use suite_args::SuiteArgs;
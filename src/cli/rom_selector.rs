@@ -2,7 +2,7 @@ use inquire::{Select, error::InquireError};
 use std::ffi::OsString;
 use walkdir::WalkDir;
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct RomSelector {
     files: Vec<OsString>,
 }
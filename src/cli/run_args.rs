@@ -0,0 +1,119 @@
+use crate::capture::DEFAULT_SCREENSHOT_SCALE;
+use crate::capture::frame::MAX_SCALE;
+use crate::cli::args::DEFAULT_HEADLESS_FRAMES;
+use crate::debugger::symbols::parse_address;
+use crate::emulator::{ErrorPolicy, Platform, RngAlgorithm};
+use crate::runner::KeyPress;
+use crate::{DEFAULT_TIMER_HZ, MAX_TIMER_HZ, MIN_TIMER_HZ};
+use clap::Parser;
+use std::path::PathBuf;
+
+/* Command line arguments of the chip8-run binary */
+#[derive(Debug, Parser)]
+#[command(
+    name = "chip8-run",
+    version,
+    about = "Runs a CHIP-8 ROM without a window and reports the final state",
+    after_help = "Exit status: 0 when a stop condition is met, the program exits or the frame \
+                  limit is reached without stop conditions, 1 on an emulation error or a final \
+                  frame differing from --expect-hash or the --play movie, 2 when no stop \
+                  condition was met in time"
+)]
+pub struct RunArgs {
+    /// ROM to run
+    pub rom: PathBuf,
+
+    /// Platform profile: chip8, vip, chip48, schip10, schip11 or xochip
+    #[arg(long, default_value_t = Platform::Chip8)]
+    pub platform: Platform,
+
+    /// Instructions executed per frame [default: the platform's speed]
+    #[arg(long)]
+    pub speed: Option<usize>,
+
+    /// On bad memory accesses, stack errors and unknown opcodes: halt, wrap or ignore
+    #[arg(long, default_value_t = ErrorPolicy::Halt)]
+    pub on_error: ErrorPolicy,
+
+    /// Delay and sound timer frequency in Hz (1-1000)
+    #[arg(
+        long,
+        default_value_t = DEFAULT_TIMER_HZ,
+        value_parser = clap::value_parser!(u32).range(MIN_TIMER_HZ as i64..=MAX_TIMER_HZ as i64)
+    )]
+    pub timer_hz: u32,
+
+    /// Seed for the CXNN random number generator, fixed so runs are reproducible
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+
+    /// CXNN random number generator: xorshift or vip-like
+    #[arg(long, default_value_t = RngAlgorithm::Xorshift)]
+    pub rng: RngAlgorithm,
+
+    /// Maximum number of frames to run
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_HEADLESS_FRAMES)]
+    pub frames: usize,
+
+    /// Stop when PC reaches ADDR, e.g. 0x3dc
+    #[arg(long, value_name = "ADDR", value_parser = parse_address)]
+    pub until_pc: Vec<u16>,
+
+    /// Stop when a frame with this hash (as printed in reports) is presented
+    #[arg(long, value_name = "HASH", value_parser = parse_hash)]
+    pub until_hash: Vec<u64>,
+
+    /// Stop when the program jumps to its own address, looping forever
+    #[arg(long)]
+    pub until_loop: bool,
+
+    /// Hold KEY (0-F) from frame FRAME, for FRAMES frames (default 6), e.g. 5@60:10
+    #[arg(long, value_name = "KEY@FRAME[:FRAMES]")]
+    pub press: Vec<KeyPress>,
+
+    /// Read key presses from FILE, whitespace separated in the --press format
+    #[arg(long, value_name = "FILE")]
+    pub keys: Option<PathBuf>,
+
+    /// Play a movie recorded with chip8 --record, in its settings, checking its final frame hash
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["frames", "press", "keys"])]
+    pub play: Option<PathBuf>,
+
+    /// Fail unless the final frame has this hash
+    #[arg(long, value_name = "HASH", value_parser = parse_hash)]
+    pub expect_hash: Option<u64>,
+
+    /// Also save the final frame as a PNG
+    #[arg(long, value_name = "FILE")]
+    pub png: Option<PathBuf>,
+
+    /// Integer scale of the --png image (1-32)
+    #[arg(long, value_name = "SCALE", default_value_t = 1, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..=MAX_SCALE as u64))]
+    pub png_scale: usize,
+
+    /// Record every frame to FILE: an animated .gif, or uncompressed .y4m or raw .rgb video
+    #[arg(long, value_name = "FILE")]
+    pub capture: Option<PathBuf>,
+
+    /// Record the beeper to a .wav FILE
+    #[arg(long, value_name = "FILE")]
+    pub capture_audio: Option<PathBuf>,
+
+    /// Integer scale of the frames written by --capture (1-32)
+    #[arg(long, value_name = "SCALE", default_value_t = DEFAULT_SCREENSHOT_SCALE, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..=MAX_SCALE as u64))]
+    pub capture_scale: usize,
+
+    /// Print the report as JSON instead of text
+    #[arg(long)]
+    pub json: bool,
+}
+
+/* Parses a frame hash, hexadecimal with an optional 0x prefix */
+pub fn parse_hash(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let hex = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .unwrap_or(s);
+    u64::from_str_radix(hex, 16).map_err(|e| format!("invalid frame hash '{s}': {e}"))
+}
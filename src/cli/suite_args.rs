@@ -0,0 +1,32 @@
+use crate::emulator::Platform;
+use clap::Parser;
+use std::path::PathBuf;
+
+/* Command line arguments of the chip8-suite binary */
+#[derive(Debug, Parser)]
+#[command(
+    name = "chip8-suite",
+    version,
+    about = "Runs Timendus' CHIP-8 test suite ROMs and reports the results they draw",
+    after_help = "ROMs are recognised by name: corax+, flags, quirks, keypad and beep. \
+                  Exit status: 0 when every test passes, 1 when one fails or a ROM \
+                  shows no results"
+)]
+pub struct SuiteArgs {
+    /// Test suite ROMs, or directories holding them
+    #[arg(required = true)]
+    pub paths: Vec<PathBuf>,
+
+    /// Platform profile the tests run on: chip8, vip, chip48, schip10, schip11 or xochip
+    #[arg(long, default_value_t = Platform::Chip8)]
+    pub platform: Platform,
+
+    /// Platform item picked in the quirks test menu (1 CHIP-8, 2 SUPER-CHIP modern,
+    /// 3 XO-CHIP, 4 SUPER-CHIP legacy) [default: matches --platform]
+    #[arg(long, value_name = "ITEM")]
+    pub quirks_menu: Option<u8>,
+
+    /// Print the report as JSON instead of text
+    #[arg(long)]
+    pub json: bool,
+}
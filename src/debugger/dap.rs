@@ -0,0 +1,586 @@
+use crate::CHIP8;
+use crate::debugger::symbols::{SymbolMap, parse_address};
+use crate::debugger::{Debugger, StopReason};
+use crate::emulator::mnemonics::disassemble;
+use crate::emulator::{Platform, Rng};
+use crate::frontend::Frontend;
+use serde_json::{Value, json};
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, RecvTimeoutError, TryRecvError};
+use std::time::Duration;
+
+/* The CHIP-8 has a single thread of execution */
+const THREAD_ID: i64 = 1;
+/* variablesReference of the two scopes */
+const REGISTERS_REF: i64 = 1;
+const STACK_REF: i64 = 2;
+/* Pause between polls for requests while the program is stopped */
+const IDLE_POLL: Duration = Duration::from_millis(10);
+
+/* The request that set a breakpoint, each request only replaces its own */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BreakpointSet {
+    Line,
+    Function,
+    Instruction,
+}
+
+/* Debug Adapter Protocol server, editors talk to it over stdio through Content-Length framed JSON */
+#[derive(Debug, Default)]
+pub struct DapServer {
+    debugger: Option<Debugger>,
+    symbols: Option<SymbolMap>,
+    /* ROM path given to launch */
+    program: Option<PathBuf>,
+    /* Sequence number of the next message we send */
+    seq: i64,
+    stop_on_entry: bool,
+    running: bool,
+    line_breakpoints: Vec<u16>,
+    function_breakpoints: Vec<u16>,
+    instruction_breakpoints: Vec<u16>,
+}
+impl DapServer {
+    pub fn new() -> DapServer {
+        DapServer {
+            seq: 1,
+            ..Default::default()
+        }
+    }
+    /* Starts the server with a machine already loaded, launch then only sets options */
+    pub fn with_debugger(debugger: Debugger) -> DapServer {
+        DapServer {
+            debugger: Some(debugger),
+            ..DapServer::new()
+        }
+    }
+    pub fn debugger(&self) -> Option<&Debugger> {
+        self.debugger.as_ref()
+    }
+    /* Serves requests read from input until the client disconnects, presenting the display once per frame */
+    pub fn serve<R, W, F>(&mut self, input: R, mut output: W, frontend: &mut F) -> io::Result<()>
+    where
+        R: BufRead + Send + 'static,
+        W: Write,
+        F: Frontend,
+    {
+        /* Requests are read on their own thread so that pause works while the program runs */
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut input = input;
+            while let Ok(Some(message)) = read_message(&mut input) {
+                if sender.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+        loop {
+            let message = if self.running {
+                match receiver.try_recv() {
+                    Ok(message) => Some(message),
+                    Err(TryRecvError::Empty) => None,
+                    Err(TryRecvError::Disconnected) => return Ok(()),
+                }
+            } else {
+                match receiver.recv_timeout(IDLE_POLL) {
+                    Ok(message) => Some(message),
+                    Err(RecvTimeoutError::Timeout) => None,
+                    Err(RecvTimeoutError::Disconnected) => return Ok(()),
+                }
+            };
+            if let Some(message) = message
+                && !self.handle(&message, &mut output)?
+            {
+                return Ok(());
+            }
+            let Some(debugger) = &mut self.debugger else {
+                continue;
+            };
+            frontend.poll_input(debugger.chip8_mut().keypad_mut());
+            if self.running {
+                let frame = debugger.chip8().cycles_per_frame() as u64;
+                match debugger.resume(Some(frame)) {
+                    StopReason::Limit => {}
+                    reason => {
+                        self.running = false;
+                        self.stopped(reason, &mut output)?;
+                    }
+                }
+            }
+            if let Some(debugger) = &self.debugger {
+                frontend.present(debugger.chip8().display());
+            }
+            if frontend.should_quit() {
+                self.event("terminated", json!({}), &mut output)?;
+                return Ok(());
+            }
+        }
+    }
+
+    /* Handles a request, returns false once the client disconnected */
+    fn handle<W: Write>(&mut self, message: &Value, output: &mut W) -> io::Result<bool> {
+        if message["type"] != "request" {
+            return Ok(true);
+        }
+        let command = message["command"].as_str().unwrap_or_default();
+        let args = &message["arguments"];
+        let result = match command {
+            "initialize" => Ok(json!({
+                "supportsConfigurationDoneRequest": true,
+                "supportsFunctionBreakpoints": true,
+                "supportsInstructionBreakpoints": true,
+                "supportsSetVariable": true,
+                "supportsStepBack": true,
+                "supportsTerminateRequest": true,
+            })),
+            "launch" => self.launch(args),
+            "configurationDone" => Ok(Value::Null),
+            "setBreakpoints" => self.set_breakpoints(args),
+            "setFunctionBreakpoints" => self.set_function_breakpoints(args),
+            "setInstructionBreakpoints" => self.set_instruction_breakpoints(args),
+            "setExceptionBreakpoints" => Ok(json!({ "breakpoints": [] })),
+            "threads" => Ok(json!({ "threads": [{ "id": THREAD_ID, "name": "CHIP-8" }] })),
+            "stackTrace" => self.stack_trace(),
+            "scopes" => Ok(json!({ "scopes": [
+                { "name": "Registers", "variablesReference": REGISTERS_REF, "expensive": false },
+                { "name": "Stack", "variablesReference": STACK_REF, "expensive": false },
+            ]})),
+            "variables" => self.variables(args),
+            "setVariable" => self.set_variable(args),
+            "continue" => {
+                self.running = self.debugger.is_some();
+                Ok(json!({ "allThreadsContinued": true }))
+            }
+            "pause" => Ok(Value::Null),
+            "next" | "stepIn" | "stepOut" | "stepBack" | "reverseContinue" => {
+                match &self.debugger {
+                    Some(_) => Ok(Value::Null),
+                    None => Err("no program launched".to_string()),
+                }
+            }
+            "disconnect" | "terminate" => Ok(Value::Null),
+            _ => Err(format!("unsupported request '{command}'")),
+        };
+        match result {
+            Ok(body) => self.respond(message, true, body, None, output)?,
+            Err(error) => self.respond(message, false, Value::Null, Some(error), output)?,
+        }
+        /* Events that must follow their response */
+        match command {
+            "initialize" => self.event("initialized", json!({}), output)?,
+            "configurationDone" if self.stop_on_entry => {
+                self.stop_event("entry", None, output)?;
+            }
+            "configurationDone" => self.running = self.debugger.is_some(),
+            "pause" if self.running => {
+                self.running = false;
+                self.stop_event("pause", None, output)?;
+            }
+            "next" | "stepIn" | "stepOut" | "stepBack" | "reverseContinue" => {
+                self.running = false;
+                if let Some(debugger) = &mut self.debugger {
+                    let reason = match command {
+                        "next" => debugger.step_over(),
+                        "stepIn" => debugger.step(),
+                        "stepBack" => debugger.step_back(),
+                        "reverseContinue" => debugger.reverse_continue(),
+                        _ => debugger.step_out(),
+                    };
+                    self.stopped(reason, output)?;
+                }
+            }
+            "disconnect" | "terminate" => {
+                self.event("terminated", json!({}), output)?;
+                return Ok(false);
+            }
+            _ => {}
+        }
+        Ok(true)
+    }
+    fn launch(&mut self, args: &Value) -> Result<Value, String> {
+        self.stop_on_entry = args["stopOnEntry"].as_bool().unwrap_or(false);
+        if let Some(path) = args["symbols"].as_str() {
+            let symbols = SymbolMap::load(path).map_err(|e| format!("symbols {path}: {e}"))?;
+            self.symbols = Some(symbols);
+        }
+        if let Some(program) = args["program"].as_str() {
+            self.program = Some(PathBuf::from(program));
+        }
+        if self.debugger.is_some() {
+            return Ok(Value::Null);
+        }
+        let program = self
+            .program
+            .clone()
+            .ok_or_else(|| "launch needs a 'program'".to_string())?;
+        let platform = match args["platform"].as_str() {
+            Some(name) => name.parse::<Platform>().map_err(|e| e.to_string())?,
+            None => Platform::default(),
+        };
+        let mut chip8 = CHIP8::with_platform(platform);
+        if let Some(speed) = args["speed"].as_u64() {
+            chip8.set_cycles_per_frame(speed as usize);
+        }
+        if let Some(seed) = args["seed"].as_u64() {
+            chip8.set_rng(Rng::from_seed(seed));
+        }
+        chip8
+            .load(&program)
+            .map_err(|e| format!("{}: {e}", program.display()))?;
+        self.debugger = Some(Debugger::new(chip8));
+        Ok(Value::Null)
+    }
+    /* Source line breakpoints, resolved through the symbol map */
+    fn set_breakpoints(&mut self, args: &Value) -> Result<Value, String> {
+        self.clear_breakpoints(BreakpointSet::Line)?;
+        let debugger = self.debugger.as_mut().ok_or("no program launched")?;
+        let requested: Vec<u32> = args["breakpoints"]
+            .as_array()
+            .map(|breakpoints| {
+                breakpoints
+                    .iter()
+                    .filter_map(|b| b["line"].as_u64())
+                    .map(|line| line as u32)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut addrs = Vec::new();
+        let breakpoints: Vec<Value> = requested
+            .iter()
+            .map(|&line| {
+                match self
+                    .symbols
+                    .as_ref()
+                    .and_then(|symbols| symbols.address_of_line(line))
+                {
+                    Some((line, addr)) => {
+                        debugger.add_breakpoint(addr);
+                        addrs.push(addr);
+                        json!({ "verified": true, "line": line, "instructionReference": address(addr) })
+                    }
+                    None => json!({
+                        "verified": false,
+                        "line": line,
+                        "message": "no symbol map entry for this line",
+                    }),
+                }
+            })
+            .collect();
+        self.line_breakpoints = addrs;
+        Ok(json!({ "breakpoints": breakpoints }))
+    }
+    /* Breakpoints on labels from the symbol map or on addresses such as 0x20a */
+    fn set_function_breakpoints(&mut self, args: &Value) -> Result<Value, String> {
+        let names: Vec<String> = args["breakpoints"]
+            .as_array()
+            .map(|breakpoints| {
+                breakpoints
+                    .iter()
+                    .filter_map(|b| b["name"].as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let addrs: Vec<Option<u16>> = names
+            .iter()
+            .map(|name| {
+                self.symbols
+                    .as_ref()
+                    .and_then(|symbols| symbols.label(name))
+                    .or_else(|| parse_address(name).ok())
+            })
+            .collect();
+        Ok(json!({ "breakpoints": self.replace_breakpoints(BreakpointSet::Function, &addrs)? }))
+    }
+    fn set_instruction_breakpoints(&mut self, args: &Value) -> Result<Value, String> {
+        let addrs: Vec<Option<u16>> = args["breakpoints"]
+            .as_array()
+            .map(|breakpoints| {
+                breakpoints
+                    .iter()
+                    .map(|b| {
+                        let base = parse_address(b["instructionReference"].as_str()?).ok()?;
+                        let offset = b["offset"].as_i64().unwrap_or(0);
+                        u16::try_from(base as i64 + offset).ok()
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(json!({ "breakpoints": self.replace_breakpoints(BreakpointSet::Instruction, &addrs)? }))
+    }
+    /* Replaces the address breakpoints of a function or instruction breakpoints request */
+    fn replace_breakpoints(
+        &mut self,
+        set: BreakpointSet,
+        addrs: &[Option<u16>],
+    ) -> Result<Vec<Value>, String> {
+        self.clear_breakpoints(set)?;
+        let debugger = self.debugger.as_mut().ok_or("no program launched")?;
+        let verified: Vec<u16> = addrs.iter().flatten().copied().collect();
+        for &addr in &verified {
+            debugger.add_breakpoint(addr);
+        }
+        *self.breakpoints_mut(set) = verified;
+        Ok(addrs
+            .iter()
+            .map(|addr| match addr {
+                Some(addr) => json!({ "verified": true, "instructionReference": address(*addr) }),
+                None => json!({ "verified": false, "message": "unknown label or address" }),
+            })
+            .collect())
+    }
+    /* Removes the breakpoints a request set before, keeping those another request still holds */
+    fn clear_breakpoints(&mut self, set: BreakpointSet) -> Result<(), String> {
+        let previous = std::mem::take(self.breakpoints_mut(set));
+        let held: Vec<u16> = [
+            BreakpointSet::Line,
+            BreakpointSet::Function,
+            BreakpointSet::Instruction,
+        ]
+        .into_iter()
+        .filter(|&other| other != set)
+        .flat_map(|other| self.breakpoints_mut(other).clone())
+        .collect();
+        let debugger = self.debugger.as_mut().ok_or("no program launched")?;
+        for addr in previous {
+            if !held.contains(&addr) {
+                debugger.remove_breakpoint(addr);
+            }
+        }
+        Ok(())
+    }
+    fn breakpoints_mut(&mut self, set: BreakpointSet) -> &mut Vec<u16> {
+        match set {
+            BreakpointSet::Line => &mut self.line_breakpoints,
+            BreakpointSet::Function => &mut self.function_breakpoints,
+            BreakpointSet::Instruction => &mut self.instruction_breakpoints,
+        }
+    }
+    /* Current PC, then one frame per CALL on the stack, innermost first */
+    fn stack_trace(&self) -> Result<Value, String> {
+        let debugger = self.debugger.as_ref().ok_or("no program launched")?;
+        let mut pcs = vec![debugger.pc()];
+        /* Return addresses point after the CALL, the frame is at the CALL itself */
+        pcs.extend(
+            debugger
+                .chip8()
+                .memory()
+                .STACK
+                .iter()
+                .rev()
+                .map(|ret| ret.wrapping_sub(2)),
+        );
+        let frames: Vec<Value> = pcs
+            .iter()
+            .enumerate()
+            .map(|(id, &pc)| self.frame(id, pc, debugger))
+            .collect();
+        Ok(json!({ "stackFrames": frames, "totalFrames": frames.len() }))
+    }
+    fn frame(&self, id: usize, pc: u16, debugger: &Debugger) -> Value {
+        let text = match *debugger.read_memory(pc, 2) {
+            [hi, lo] => disassemble(u16::from_be_bytes([hi, lo])),
+            _ => "??".to_string(),
+        };
+        let label = self
+            .symbols
+            .as_ref()
+            .and_then(|symbols| symbols.label_before(pc))
+            .map(|(label, offset)| format!("{label}+{offset:#x}: "))
+            .unwrap_or_default();
+        let mut frame = json!({
+            "id": id,
+            "name": format!("{label}{pc:#06x}  {text}"),
+            "line": 0,
+            "column": 0,
+            "instructionPointerReference": address(pc),
+        });
+        if let Some(symbols) = &self.symbols
+            && let (Some(source), Some(line)) = (&symbols.source, symbols.line_of(pc))
+        {
+            frame["source"] = json!({ "path": source });
+            frame["line"] = json!(line);
+            frame["column"] = json!(1);
+        }
+        frame
+    }
+    fn variables(&self, args: &Value) -> Result<Value, String> {
+        let debugger = self.debugger.as_ref().ok_or("no program launched")?;
+        let cpu = debugger.chip8().cpu();
+        let variables: Vec<Value> = match args["variablesReference"].as_i64() {
+            Some(REGISTERS_REF) => {
+                let mut registers: Vec<(String, String)> = (0..16)
+                    .map(|x| (format!("V{x:X}"), format!("{:#04x}", cpu.V[x])))
+                    .collect();
+                registers.extend([
+                    ("I".to_string(), format!("{:#06x}", cpu.I)),
+                    ("PC".to_string(), format!("{:#06x}", cpu.PROGRAM_COUNTER)),
+                    ("SP".to_string(), debugger.call_depth().to_string()),
+                    ("DT".to_string(), format!("{:#04x}", cpu.D_TIMER)),
+                    ("ST".to_string(), format!("{:#04x}", cpu.S_TIMER)),
+                ]);
+                registers
+                    .into_iter()
+                    .map(|(name, value)| json!({ "name": name, "value": value, "variablesReference": 0 }))
+                    .collect()
+            }
+            Some(STACK_REF) => debugger
+                .chip8()
+                .memory()
+                .STACK
+                .iter()
+                .enumerate()
+                .rev()
+                .map(|(depth, ret)| {
+                    json!({ "name": format!("#{depth}"), "value": address(*ret), "variablesReference": 0 })
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        Ok(json!({ "variables": variables }))
+    }
+    fn set_variable(&mut self, args: &Value) -> Result<Value, String> {
+        let debugger = self.debugger.as_mut().ok_or("no program launched")?;
+        if args["variablesReference"].as_i64() != Some(REGISTERS_REF) {
+            return Err("only registers can be set".to_string());
+        }
+        let name = args["name"].as_str().unwrap_or_default();
+        let value = args["value"]
+            .as_str()
+            .and_then(|value| parse_address(value).ok())
+            .ok_or("invalid value")?;
+        let cpu = debugger.chip8_mut().cpu_mut();
+        let byte = u8::try_from(value).map_err(|_| "value out of range");
+        let shown = match name {
+            "I" => {
+                cpu.I = value;
+                format!("{value:#06x}")
+            }
+            "PC" => {
+                cpu.PROGRAM_COUNTER = value;
+                format!("{value:#06x}")
+            }
+            "DT" => {
+                cpu.D_TIMER = byte?;
+                format!("{value:#04x}")
+            }
+            "ST" => {
+                cpu.S_TIMER = byte?;
+                format!("{value:#04x}")
+            }
+            _ => {
+                let x = name
+                    .strip_prefix('V')
+                    .and_then(|x| u8::from_str_radix(x, 16).ok())
+                    .filter(|&x| x < 16)
+                    .ok_or("only V0-VF, I, PC, DT and ST can be set")?;
+                cpu.V[x as usize] = byte?;
+                format!("{value:#04x}")
+            }
+        };
+        Ok(json!({ "value": shown }))
+    }
+
+    /* Reports why execution stopped */
+    fn stopped<W: Write>(&mut self, reason: StopReason, output: &mut W) -> io::Result<()> {
+        match reason {
+            StopReason::Step
+            | StopReason::Reached(_)
+            | StopReason::Limit
+            | StopReason::HistoryStart => self.stop_event("step", None, output),
+            StopReason::Breakpoint(_) => self.stop_event("breakpoint", None, output),
+            StopReason::Watchpoint { .. } | StopReason::RegisterChanged { .. } => {
+                self.stop_event("data breakpoint", None, output)
+            }
+            StopReason::Exited => {
+                self.event("exited", json!({ "exitCode": 0 }), output)?;
+                self.event("terminated", json!({}), output)
+            }
+            StopReason::Fault(error) => {
+                let text = error.to_string();
+                self.event(
+                    "output",
+                    json!({ "category": "stderr", "output": format!("{text}\n") }),
+                    output,
+                )?;
+                self.stop_event("exception", Some(text), output)
+            }
+        }
+    }
+    fn stop_event<W: Write>(
+        &mut self,
+        reason: &str,
+        text: Option<String>,
+        output: &mut W,
+    ) -> io::Result<()> {
+        let mut body =
+            json!({ "reason": reason, "threadId": THREAD_ID, "allThreadsStopped": true });
+        if let Some(text) = text {
+            body["text"] = json!(text);
+        }
+        self.event("stopped", body, output)
+    }
+    fn event<W: Write>(&mut self, event: &str, body: Value, output: &mut W) -> io::Result<()> {
+        let message = json!({ "seq": self.seq, "type": "event", "event": event, "body": body });
+        self.send(&message, output)
+    }
+    fn respond<W: Write>(
+        &mut self,
+        request: &Value,
+        success: bool,
+        body: Value,
+        error: Option<String>,
+        output: &mut W,
+    ) -> io::Result<()> {
+        let mut message = json!({
+            "seq": self.seq,
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": success,
+        });
+        if !body.is_null() {
+            message["body"] = body;
+        }
+        if let Some(error) = error {
+            message["message"] = json!(error);
+        }
+        self.send(&message, output)
+    }
+    fn send<W: Write>(&mut self, message: &Value, output: &mut W) -> io::Result<()> {
+        self.seq += 1;
+        let text = message.to_string();
+        write!(output, "Content-Length: {}\r\n\r\n{text}", text.len())?;
+        output.flush()
+    }
+}
+
+/* Reads one Content-Length framed message, None at end of input */
+fn read_message<R: BufRead>(input: &mut R) -> io::Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.eq_ignore_ascii_case("Content-Length")
+        {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let length = length
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length"))?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+fn address(addr: u16) -> String {
+    format!("{addr:#06x}")
+}
@@ -0,0 +1,493 @@
+use crate::debugger::{Debugger, StopReason, WatchKind, Watchpoint};
+use crate::emulator::errors::{CpuError, EmuError};
+use crate::frontend::Frontend;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/* Register numbers as seen by GDB: V0-VF, then I, PC, SP, DT and ST */
+const NUM_REGISTERS: usize = 21;
+const REG_I: usize = 16;
+const REG_PC: usize = 17;
+const REG_SP: usize = 18;
+const REG_DT: usize = 19;
+const REG_ST: usize = 20;
+/* Largest packet GDB may send us, advertised in qSupported */
+const PACKET_SIZE: usize = 0x4000;
+/* Pause between socket polls while the target is stopped */
+const IDLE_POLL: Duration = Duration::from_millis(10);
+
+/* Unix signal numbers used in stop replies */
+const SIGINT: u8 = 2;
+const SIGILL: u8 = 4;
+const SIGTRAP: u8 = 5;
+const SIGSEGV: u8 = 11;
+
+const TARGET_XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE target SYSTEM "gdb-target.dtd">
+<target version="1.0">
+  <feature name="org.chip8.core">
+    <reg name="v0" bitsize="8" regnum="0"/>
+    <reg name="v1" bitsize="8"/>
+    <reg name="v2" bitsize="8"/>
+    <reg name="v3" bitsize="8"/>
+    <reg name="v4" bitsize="8"/>
+    <reg name="v5" bitsize="8"/>
+    <reg name="v6" bitsize="8"/>
+    <reg name="v7" bitsize="8"/>
+    <reg name="v8" bitsize="8"/>
+    <reg name="v9" bitsize="8"/>
+    <reg name="va" bitsize="8"/>
+    <reg name="vb" bitsize="8"/>
+    <reg name="vc" bitsize="8"/>
+    <reg name="vd" bitsize="8"/>
+    <reg name="ve" bitsize="8"/>
+    <reg name="vf" bitsize="8"/>
+    <reg name="i" bitsize="16" type="data_ptr"/>
+    <reg name="pc" bitsize="16" type="code_ptr"/>
+    <reg name="sp" bitsize="8"/>
+    <reg name="dt" bitsize="8"/>
+    <reg name="st" bitsize="8"/>
+  </feature>
+</target>
+"#;
+
+/* What the server does after handling a packet */
+enum Action {
+    Reply(String),
+    Continue,
+    Step,
+    /* Reply, then close the connection */
+    Close(Option<String>),
+}
+
+/* Input read from the client */
+enum Input {
+    Packet(String),
+    BadChecksum,
+    Interrupt,
+}
+
+/* GDB remote serial protocol server, 16 bit registers are sent little endian and SP is read-only */
+#[derive(Debug)]
+pub struct GdbServer {
+    debugger: Debugger,
+    /* Client asked for QStartNoAckMode */
+    no_ack: bool,
+    /* Client understands swbreak stop reasons */
+    swbreak: bool,
+}
+impl GdbServer {
+    pub fn new(debugger: Debugger) -> GdbServer {
+        GdbServer {
+            debugger,
+            no_ack: false,
+            swbreak: false,
+        }
+    }
+    #[inline]
+    pub fn debugger(&self) -> &Debugger {
+        &self.debugger
+    }
+    pub fn into_inner(self) -> Debugger {
+        self.debugger
+    }
+    /* Waits for one GDB connection on addr and serves it until GDB detaches */
+    pub fn listen<A: ToSocketAddrs, F: Frontend>(
+        &mut self,
+        addr: A,
+        frontend: &mut F,
+    ) -> io::Result<()> {
+        let listener = TcpListener::bind(addr)?;
+        let (stream, _) = listener.accept()?;
+        self.serve(stream, frontend)
+    }
+    /* Serves a connected client, presenting the display once per frame while the ROM runs */
+    pub fn serve<F: Frontend>(
+        &mut self,
+        mut stream: TcpStream,
+        frontend: &mut F,
+    ) -> io::Result<()> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+        self.no_ack = false;
+        let mut buffer = Vec::new();
+        let mut running = false;
+        while !frontend.should_quit() {
+            let mut chunk = [0; 4096];
+            match stream.read(&mut chunk) {
+                /* Client disconnected */
+                Ok(0) => break,
+                Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+            while let Some(input) = next_input(&mut buffer) {
+                match input {
+                    Input::Interrupt if running => {
+                        running = false;
+                        send(&mut stream, &format!("S{SIGINT:02x}"))?;
+                    }
+                    Input::Interrupt => {}
+                    Input::BadChecksum => write_all(&mut stream, b"-")?,
+                    Input::Packet(packet) => {
+                        if !self.no_ack {
+                            write_all(&mut stream, b"+")?;
+                        }
+                        match self.handle(&packet) {
+                            Action::Reply(reply) => send(&mut stream, &reply)?,
+                            Action::Continue => running = true,
+                            Action::Step => {
+                                let reason = self.debugger.step();
+                                send(&mut stream, &self.stop_reply(reason))?;
+                            }
+                            Action::Close(reply) => {
+                                if let Some(reply) = reply {
+                                    send(&mut stream, &reply)?;
+                                }
+                                return Ok(());
+                            }
+                        }
+                    }
+                }
+            }
+            frontend.poll_input(self.debugger.chip8_mut().keypad_mut());
+            if running {
+                let frame = self.debugger.chip8().cycles_per_frame() as u64;
+                match self.debugger.resume(Some(frame)) {
+                    StopReason::Limit => {}
+                    reason => {
+                        running = false;
+                        send(&mut stream, &self.stop_reply(reason))?;
+                    }
+                }
+            } else {
+                std::thread::sleep(IDLE_POLL);
+            }
+            frontend.present(self.debugger.chip8().display());
+        }
+        Ok(())
+    }
+
+    /* Handles a single packet */
+    fn handle(&mut self, packet: &str) -> Action {
+        let (command, args) = packet.split_at(packet.chars().next().map_or(0, char::len_utf8));
+        let reply = match command {
+            "?" => format!("S{SIGTRAP:02x}"),
+            "g" => self.read_registers(),
+            "G" => self.write_registers(args),
+            "p" => self.read_register(args),
+            "P" => self.write_register(args),
+            "m" => self.read_memory(args),
+            "M" => self.write_memory(args),
+            "Z" | "z" => self.breakpoint(command == "Z", args),
+            "c" => {
+                if let Some(addr) = parse_hex(args) {
+                    self.debugger.chip8_mut().cpu_mut().PROGRAM_COUNTER = addr as u16;
+                }
+                return Action::Continue;
+            }
+            "s" => {
+                if let Some(addr) = parse_hex(args) {
+                    self.debugger.chip8_mut().cpu_mut().PROGRAM_COUNTER = addr as u16;
+                }
+                return Action::Step;
+            }
+            /* Reverse execution runs from the history, so it completes before replying */
+            "b" => {
+                let reason = match args {
+                    "c" => self.debugger.reverse_continue(),
+                    "s" => self.debugger.step_back(),
+                    _ => return Action::Reply(String::new()),
+                };
+                self.stop_reply(reason)
+            }
+            "H" => "OK".to_string(),
+            "T" => "OK".to_string(),
+            "D" => return Action::Close(Some("OK".to_string())),
+            "k" => return Action::Close(None),
+            "q" | "Q" => self.query(packet),
+            /* Unsupported packets get an empty reply */
+            _ => String::new(),
+        };
+        Action::Reply(reply)
+    }
+    fn query(&mut self, packet: &str) -> String {
+        if let Some(features) = packet.strip_prefix("qSupported") {
+            self.swbreak = features.contains("swbreak+");
+            return format!(
+                "PacketSize={PACKET_SIZE:x};qXfer:features:read+;swbreak+;QStartNoAckMode+;ReverseStep+;ReverseContinue+"
+            );
+        }
+        if let Some(request) = packet.strip_prefix("qXfer:features:read:target.xml:") {
+            return read_xfer(TARGET_XML, request);
+        }
+        match packet {
+            "QStartNoAckMode" => {
+                self.no_ack = true;
+                "OK".to_string()
+            }
+            "qAttached" => "1".to_string(),
+            "qC" => "QC1".to_string(),
+            "qfThreadInfo" => "m1".to_string(),
+            "qsThreadInfo" => "l".to_string(),
+            _ => String::new(),
+        }
+    }
+
+    /* Value and size in bytes of a register */
+    fn register(&self, reg: usize) -> Option<(u16, usize)> {
+        let cpu = self.debugger.chip8().cpu();
+        Some(match reg {
+            0..=15 => (cpu.V[reg] as u16, 1),
+            REG_I => (cpu.I, 2),
+            REG_PC => (cpu.PROGRAM_COUNTER, 2),
+            REG_SP => (self.debugger.call_depth() as u16, 1),
+            REG_DT => (cpu.D_TIMER as u16, 1),
+            REG_ST => (cpu.S_TIMER as u16, 1),
+            _ => return None,
+        })
+    }
+    fn set_register(&mut self, reg: usize, value: u16) -> bool {
+        let cpu = self.debugger.chip8_mut().cpu_mut();
+        match reg {
+            0..=15 => cpu.V[reg] = value as u8,
+            REG_I => cpu.I = value,
+            REG_PC => cpu.PROGRAM_COUNTER = value,
+            /* The stack lives in Memory, SP only mirrors its depth */
+            REG_SP => {}
+            REG_DT => cpu.D_TIMER = value as u8,
+            REG_ST => cpu.S_TIMER = value as u8,
+            _ => return false,
+        }
+        true
+    }
+    fn read_registers(&self) -> String {
+        (0..NUM_REGISTERS)
+            .filter_map(|reg| self.register(reg))
+            .map(|(value, size)| encode_le(value, size))
+            .collect()
+    }
+    fn write_registers(&mut self, args: &str) -> String {
+        let Some(bytes) = decode_hex(args) else {
+            return error(1);
+        };
+        let mut offset = 0;
+        for reg in 0..NUM_REGISTERS {
+            let Some((_, size)) = self.register(reg) else {
+                break;
+            };
+            let Some(value) = bytes.get(offset..offset + size) else {
+                break;
+            };
+            self.set_register(reg, decode_le(value));
+            offset += size;
+        }
+        "OK".to_string()
+    }
+    fn read_register(&self, args: &str) -> String {
+        match parse_hex(args).and_then(|reg| self.register(reg)) {
+            Some((value, size)) => encode_le(value, size),
+            None => error(1),
+        }
+    }
+    fn write_register(&mut self, args: &str) -> String {
+        let parsed = args.split_once('=').and_then(|(reg, value)| {
+            let reg = parse_hex(reg)?;
+            let bytes = decode_hex(value)?;
+            Some((reg, decode_le(&bytes)))
+        });
+        match parsed {
+            Some((reg, value)) if self.set_register(reg, value) => "OK".to_string(),
+            _ => error(1),
+        }
+    }
+    fn read_memory(&self, args: &str) -> String {
+        let Some((addr, len)) = parse_addr_len(args) else {
+            return error(1);
+        };
+        let Ok(addr) = u16::try_from(addr) else {
+            return error(1);
+        };
+        match self.debugger.read_memory(addr, len.min(PACKET_SIZE / 2)) {
+            [] if len > 0 => error(1),
+            bytes => bytes.iter().map(|b| format!("{b:02x}")).collect(),
+        }
+    }
+    fn write_memory(&mut self, args: &str) -> String {
+        let parsed = args.split_once(':').and_then(|(range, data)| {
+            let (addr, len) = parse_addr_len(range)?;
+            let data = decode_hex(data)?;
+            (data.len() == len).then_some((addr, data))
+        });
+        let Some((addr, data)) = parsed else {
+            return error(1);
+        };
+        let Some(end) = addr.checked_add(data.len()) else {
+            return error(1);
+        };
+        let ram = &mut self.debugger.chip8_mut().memory_mut().RAM;
+        match ram.get_mut(addr..end) {
+            Some(target) => {
+                target.copy_from_slice(&data);
+                "OK".to_string()
+            }
+            None => error(1),
+        }
+    }
+    /* Z/z type,addr,kind: 0 and 1 are PC breakpoints, 2 to 4 write, read and access watchpoints */
+    fn breakpoint(&mut self, insert: bool, args: &str) -> String {
+        let mut fields = args.split(',');
+        let (Some(kind), Some(addr), Some(len)) = (fields.next(), fields.next(), fields.next())
+        else {
+            return error(1);
+        };
+        let (Some(addr), Some(len)) = (parse_hex(addr), parse_hex(len)) else {
+            return error(1);
+        };
+        let Ok(addr) = u16::try_from(addr) else {
+            return error(1);
+        };
+        let watch = match kind {
+            "0" | "1" => {
+                if insert {
+                    self.debugger.add_breakpoint(addr);
+                } else {
+                    self.debugger.remove_breakpoint(addr);
+                }
+                return "OK".to_string();
+            }
+            "2" => WatchKind::Write,
+            "3" => WatchKind::Read,
+            "4" => WatchKind::ReadWrite,
+            _ => return String::new(),
+        };
+        let end = addr.saturating_add((len.max(1) - 1) as u16);
+        let watchpoint = Watchpoint::new(addr, end, watch);
+        if insert {
+            self.debugger.add_watchpoint(watchpoint);
+        } else if let Some(index) = self
+            .debugger
+            .watchpoints()
+            .iter()
+            .position(|&w| w == watchpoint)
+        {
+            self.debugger.remove_watchpoint(index);
+        }
+        "OK".to_string()
+    }
+    fn stop_reply(&self, reason: StopReason) -> String {
+        match reason {
+            StopReason::Breakpoint(_) if self.swbreak => format!("T{SIGTRAP:02x}swbreak:;"),
+            StopReason::Watchpoint {
+                watchpoint, access, ..
+            } => {
+                let kind = match watchpoint.kind {
+                    WatchKind::Write => "watch",
+                    WatchKind::Read => "rwatch",
+                    WatchKind::ReadWrite => "awatch",
+                };
+                format!("T{SIGTRAP:02x}{kind}:{:x};", access.addr)
+            }
+            StopReason::HistoryStart => format!("T{SIGTRAP:02x}replaylog:begin;"),
+            StopReason::Exited => "W00".to_string(),
+            StopReason::Fault(EmuError::Cpu {
+                source: CpuError::Opcode(_),
+                ..
+            }) => format!("S{SIGILL:02x}"),
+            StopReason::Fault(_) => format!("S{SIGSEGV:02x}"),
+            _ => format!("S{SIGTRAP:02x}"),
+        }
+    }
+}
+
+/* Takes the next packet, ack or interrupt from the receive buffer */
+fn next_input(buffer: &mut Vec<u8>) -> Option<Input> {
+    loop {
+        match buffer.first()? {
+            0x03 => {
+                buffer.remove(0);
+                return Some(Input::Interrupt);
+            }
+            b'$' => break,
+            /* Acks and line noise */
+            _ => {
+                buffer.remove(0);
+            }
+        }
+    }
+    let end = buffer.iter().position(|&b| b == b'#')?;
+    if buffer.len() < end + 3 {
+        return None;
+    }
+    let packet: Vec<u8> = buffer.drain(..end + 3).collect();
+    let data = &packet[1..end];
+    let checksum = std::str::from_utf8(&packet[end + 1..])
+        .ok()
+        .and_then(|s| u8::from_str_radix(s, 16).ok());
+    if checksum != Some(checksum_of(data)) {
+        return Some(Input::BadChecksum);
+    }
+    Some(Input::Packet(String::from_utf8_lossy(data).into_owned()))
+}
+fn checksum_of(data: &[u8]) -> u8 {
+    data.iter().fold(0, |sum, &b| sum.wrapping_add(b))
+}
+fn send(stream: &mut TcpStream, data: &str) -> io::Result<()> {
+    let packet = format!("${data}#{:02x}", checksum_of(data.as_bytes()));
+    write_all(stream, packet.as_bytes())
+}
+/* write_all on a non-blocking socket, retrying while the send buffer is full */
+fn write_all(stream: &mut TcpStream, mut data: &[u8]) -> io::Result<()> {
+    while !data.is_empty() {
+        match stream.write(data) {
+            Ok(0) => return Err(ErrorKind::WriteZero.into()),
+            Ok(n) => data = &data[n..],
+            Err(e) if e.kind() == ErrorKind::WouldBlock => std::thread::sleep(IDLE_POLL),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+fn error(code: u8) -> String {
+    format!("E{code:02x}")
+}
+fn parse_hex(s: &str) -> Option<usize> {
+    usize::from_str_radix(s, 16).ok()
+}
+fn parse_addr_len(s: &str) -> Option<(usize, usize)> {
+    let (addr, len) = s.split_once(',')?;
+    Some((parse_hex(addr)?, parse_hex(len)?))
+}
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+fn encode_le(value: u16, size: usize) -> String {
+    value.to_le_bytes()[..size]
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+fn decode_le(bytes: &[u8]) -> u16 {
+    bytes
+        .iter()
+        .take(2)
+        .rev()
+        .fold(0, |value, &b| (value << 8) | b as u16)
+}
+/* Answers qXfer offset,length requests, 'l' marks the last chunk */
+fn read_xfer(document: &str, request: &str) -> String {
+    let Some((offset, length)) = parse_addr_len(request) else {
+        return error(0);
+    };
+    let start = offset.min(document.len());
+    let end = start.saturating_add(length).min(document.len());
+    let marker = if end == document.len() { 'l' } else { 'm' };
+    format!("{marker}{}", &document[start..end])
+}
@@ -0,0 +1,386 @@
+pub mod dap;
+pub mod gdb;
+pub mod repl;
+pub mod symbols;
+pub mod watch;
+
+pub use dap::DapServer;
+pub use gdb::GdbServer;
+pub use repl::Repl;
+pub use symbols::SymbolMap;
+pub use watch::{Condition, RegisterBreakpoint, WatchKind, Watchpoint};
+
+use crate::CHIP8;
+use crate::emulator::errors::EmuError;
+use crate::emulator::memory::MemoryAccess;
+use crate::emulator::mnemonics::{Mnemonics, decode};
+use crate::emulator::snapshot::RewindBuffer;
+use crate::emulator::trace::{Register, Registers};
+use std::collections::BTreeSet;
+
+/* Frames of snapshots kept for stepping backwards, a minute at 60 frames per second */
+pub const DEFAULT_HISTORY_FRAMES: usize = 3600;
+
+/* Why execution returned control to the debugger */
+#[derive(Debug)]
+pub enum StopReason {
+    /* A single step or step over/out completed */
+    Step,
+    /* PC reached a breakpoint */
+    Breakpoint(u16),
+    /* An instruction accessed RAM covered by a watchpoint */
+    Watchpoint {
+        pc: u16,
+        watchpoint: Watchpoint,
+        access: MemoryAccess,
+    },
+    /* An instruction changed a register so that a register breakpoint triggered */
+    RegisterChanged {
+        pc: u16,
+        register: Register,
+        before: u16,
+        after: u16,
+    },
+    /* PC reached the run-to address */
+    Reached(u16),
+    /* The program executed 00FD */
+    Exited,
+    /* The instruction limit given to continue was reached */
+    Limit,
+    /* An instruction failed under the halt error policy */
+    Fault(EmuError),
+    /* Reverse execution reached the oldest recorded state */
+    HistoryStart,
+}
+
+/* Breakpoints and stepping on top of a CHIP8 machine */
+#[derive(Debug)]
+pub struct Debugger {
+    chip8: CHIP8,
+    breakpoints: BTreeSet<u16>,
+    watchpoints: Vec<Watchpoint>,
+    register_breakpoints: Vec<RegisterBreakpoint>,
+    /* Instructions executed in the current frame, timers tick once per frame */
+    frame_cycles: usize,
+    /* Snapshots at frame boundaries, reverse execution replays forward from them */
+    history: RewindBuffer,
+}
+impl Debugger {
+    pub fn new(chip8: CHIP8) -> Debugger {
+        let mut debugger = Debugger {
+            chip8,
+            breakpoints: BTreeSet::new(),
+            watchpoints: Vec::new(),
+            register_breakpoints: Vec::new(),
+            frame_cycles: 0,
+            history: RewindBuffer::new(DEFAULT_HISTORY_FRAMES),
+        };
+        debugger.history.push(debugger.chip8.snapshot());
+        debugger
+    }
+    /* Resets the machine, reloading the ROM, and forgets the execution history */
+    pub fn reset(&mut self) {
+        self.chip8.reset();
+        self.frame_cycles = 0;
+        self.history.clear();
+        self.history.push(self.chip8.snapshot());
+    }
+    #[inline]
+    pub fn chip8(&self) -> &CHIP8 {
+        &self.chip8
+    }
+    #[inline]
+    pub fn chip8_mut(&mut self) -> &mut CHIP8 {
+        &mut self.chip8
+    }
+    pub fn into_inner(self) -> CHIP8 {
+        self.chip8
+    }
+    #[inline]
+    pub fn pc(&self) -> u16 {
+        self.chip8.cpu().PROGRAM_COUNTER
+    }
+    /* Subroutine depth, the number of return addresses on the stack */
+    #[inline]
+    pub fn call_depth(&self) -> usize {
+        self.chip8.memory().STACK.len()
+    }
+    /* Returns false if the breakpoint was already set */
+    pub fn add_breakpoint(&mut self, addr: u16) -> bool {
+        self.breakpoints.insert(addr)
+    }
+    /* Returns false if there was no breakpoint at addr */
+    pub fn remove_breakpoint(&mut self, addr: u16) -> bool {
+        self.breakpoints.remove(&addr)
+    }
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+    pub fn breakpoints(&self) -> impl Iterator<Item = u16> + '_ {
+        self.breakpoints.iter().copied()
+    }
+    #[inline]
+    pub fn has_breakpoint(&self, addr: u16) -> bool {
+        self.breakpoints.contains(&addr)
+    }
+    pub fn add_watchpoint(&mut self, watchpoint: Watchpoint) {
+        self.watchpoints.push(watchpoint);
+    }
+    /* Removes the watchpoint at `index` in watchpoints() */
+    pub fn remove_watchpoint(&mut self, index: usize) -> Option<Watchpoint> {
+        (index < self.watchpoints.len()).then(|| self.watchpoints.remove(index))
+    }
+    #[inline]
+    pub fn watchpoints(&self) -> &[Watchpoint] {
+        &self.watchpoints
+    }
+    pub fn add_register_breakpoint(&mut self, breakpoint: RegisterBreakpoint) {
+        self.register_breakpoints.push(breakpoint);
+    }
+    /* Removes the register breakpoint at `index` in register_breakpoints() */
+    pub fn remove_register_breakpoint(&mut self, index: usize) -> Option<RegisterBreakpoint> {
+        (index < self.register_breakpoints.len()).then(|| self.register_breakpoints.remove(index))
+    }
+    #[inline]
+    pub fn register_breakpoints(&self) -> &[RegisterBreakpoint] {
+        &self.register_breakpoints
+    }
+    /* Reads `len` bytes of RAM from addr, cut short at the end of RAM */
+    pub fn read_memory(&self, addr: u16, len: usize) -> &[u8] {
+        let ram = &self.chip8.memory().RAM;
+        let start = (addr as usize).min(ram.len());
+        let end = start.saturating_add(len).min(ram.len());
+        &ram[start..end]
+    }
+    /* Decoded instruction at addr */
+    pub fn instruction_at(&self, addr: u16) -> Option<Mnemonics> {
+        match *self.read_memory(addr, 2) {
+            [hi, lo] => Some(decode(u16::from_be_bytes([hi, lo]))),
+            _ => None,
+        }
+    }
+
+    /* Executes one instruction, ticking the timers and recording history at frame boundaries */
+    fn advance(&mut self) -> Result<(), EmuError> {
+        self.chip8.step()?;
+        self.frame_cycles += 1;
+        let vblank = std::mem::take(&mut self.chip8.cpu_mut().vblank_wait);
+        if vblank || self.frame_cycles >= self.chip8.cycles_per_frame() {
+            self.frame_cycles = 0;
+            self.chip8.tick_timers();
+            self.history.push(self.chip8.snapshot());
+        }
+        Ok(())
+    }
+    /* Executes one instruction like CHIP8::run_frame would, checking watchpoints and register breakpoints */
+    fn execute(&mut self) -> Result<Option<StopReason>, EmuError> {
+        let pc = self.pc();
+        /* RAM accesses are only recorded while watchpoints are set */
+        let cpu = self.chip8.cpu_mut();
+        if self.watchpoints.is_empty() {
+            cpu.access_log = None;
+        } else {
+            cpu.access_log.get_or_insert_with(Vec::new).clear();
+        }
+        let before = Registers::capture(self.chip8.cpu());
+        self.advance()?;
+        /* Registers are compared after the timers so that timer breakpoints see them count down */
+        Ok(self.check_watchpoints(pc).or_else(|| {
+            let after = Registers::capture(self.chip8.cpu());
+            self.check_registers(pc, &before, &after)
+        }))
+    }
+    fn check_watchpoints(&self, pc: u16) -> Option<StopReason> {
+        let log = self.chip8.cpu().access_log.as_ref()?;
+        log.iter().find_map(|access| {
+            self.watchpoints
+                .iter()
+                .find(|watchpoint| watchpoint.matches(access))
+                .map(|&watchpoint| StopReason::Watchpoint {
+                    pc,
+                    watchpoint,
+                    access: *access,
+                })
+        })
+    }
+    fn check_registers(
+        &self,
+        pc: u16,
+        before: &Registers,
+        after: &Registers,
+    ) -> Option<StopReason> {
+        self.register_breakpoints.iter().find_map(|breakpoint| {
+            let register = breakpoint.register;
+            let (before, after) = (before.get(register), after.get(register));
+            breakpoint
+                .matches(before, after)
+                .then_some(StopReason::RegisterChanged {
+                    pc,
+                    register,
+                    before,
+                    after,
+                })
+        })
+    }
+    /* Executes until `done` holds, a breakpoint is hit or `limit` instructions ran */
+    fn run_until<F>(&mut self, limit: Option<u64>, mut done: F) -> StopReason
+    where
+        F: FnMut(&Debugger) -> Option<StopReason>,
+    {
+        let mut executed = 0;
+        loop {
+            if self.chip8.has_exited() {
+                return StopReason::Exited;
+            }
+            if limit.is_some_and(|limit| executed >= limit) {
+                return StopReason::Limit;
+            }
+            match self.execute() {
+                Ok(Some(hit)) => return hit,
+                Ok(None) => {}
+                Err(error) => return StopReason::Fault(error),
+            }
+            executed += 1;
+            if self.chip8.has_exited() {
+                return StopReason::Exited;
+            }
+            if let Some(reason) = done(self) {
+                return reason;
+            }
+            let pc = self.pc();
+            if self.has_breakpoint(pc) {
+                return StopReason::Breakpoint(pc);
+            }
+        }
+    }
+    /* Executes a single instruction, ignoring PC breakpoints */
+    pub fn step(&mut self) -> StopReason {
+        if self.chip8.has_exited() {
+            return StopReason::Exited;
+        }
+        match self.execute() {
+            Ok(Some(hit)) => hit,
+            Ok(None) if self.chip8.has_exited() => StopReason::Exited,
+            Ok(None) => StopReason::Step,
+            Err(error) => StopReason::Fault(error),
+        }
+    }
+    /* Like step, but runs a CALL until the subroutine returns */
+    pub fn step_over(&mut self) -> StopReason {
+        let pc = self.pc();
+        if !matches!(self.instruction_at(pc), Some(Mnemonics::CALL { .. })) {
+            return self.step();
+        }
+        let depth = self.call_depth();
+        self.run_until(None, |debugger| {
+            (debugger.call_depth() <= depth).then_some(StopReason::Step)
+        })
+    }
+    /* Runs until the current subroutine returns to its caller */
+    pub fn step_out(&mut self) -> StopReason {
+        let depth = self.call_depth();
+        /* Not in a subroutine, there is nothing to return from */
+        if depth == 0 {
+            return self.step();
+        }
+        self.run_until(None, |debugger| {
+            (debugger.call_depth() < depth).then_some(StopReason::Step)
+        })
+    }
+    /* Runs until a breakpoint is hit, at most `limit` instructions if given */
+    pub fn resume(&mut self, limit: Option<u64>) -> StopReason {
+        self.run_until(limit, |_| None)
+    }
+    /* Runs until PC reaches addr or a breakpoint is hit */
+    pub fn run_to(&mut self, addr: u16, limit: Option<u64>) -> StopReason {
+        self.run_until(limit, |debugger| {
+            (debugger.pc() == addr).then_some(StopReason::Reached(addr))
+        })
+    }
+
+    /* Returns to the state after `cycles` instructions by replaying from the closest earlier snapshot */
+    fn travel_to(&mut self, cycles: u64) -> bool {
+        if self
+            .history
+            .oldest_cycles()
+            .is_none_or(|oldest| oldest > cycles)
+        {
+            return false;
+        }
+        while self
+            .history
+            .newest_cycles()
+            .is_some_and(|newest| newest > cycles)
+        {
+            self.history.pop();
+        }
+        let Some(snapshot) = self.history.pop() else {
+            return false;
+        };
+        self.chip8.restore(&snapshot);
+        self.frame_cycles = 0;
+        self.history.push(snapshot);
+        /* The instructions ran before, so replaying them cannot fail */
+        while self.chip8.cycles() < cycles {
+            if self.advance().is_err() {
+                return false;
+            }
+        }
+        true
+    }
+    /* Undoes the last instruction */
+    pub fn step_back(&mut self) -> StopReason {
+        let cycles = self.chip8.cycles();
+        if cycles == 0 || !self.travel_to(cycles - 1) {
+            return StopReason::HistoryStart;
+        }
+        StopReason::Step
+    }
+    /* Runs backwards to the last time PC was at a breakpoint */
+    pub fn reverse_continue(&mut self) -> StopReason {
+        let mut end = self.chip8.cycles();
+        loop {
+            /* Replay the interval from the closest snapshot before `end`, noting breakpoint hits */
+            while self
+                .history
+                .newest_cycles()
+                .is_some_and(|newest| newest >= end)
+            {
+                if self.history.len() == 1 {
+                    break;
+                }
+                self.history.pop();
+            }
+            let Some(snapshot) = self.history.pop() else {
+                return StopReason::HistoryStart;
+            };
+            let start = snapshot.cycles;
+            self.chip8.restore(&snapshot);
+            self.frame_cycles = 0;
+            self.history.push(snapshot);
+            let mut last_hit = None;
+            while self.chip8.cycles() < end {
+                if self.has_breakpoint(self.pc()) {
+                    last_hit = Some(self.chip8.cycles());
+                }
+                if self.advance().is_err() {
+                    break;
+                }
+            }
+            if let Some(hit) = last_hit
+                && self.travel_to(hit)
+            {
+                return StopReason::Breakpoint(self.pc());
+            }
+            if self
+                .history
+                .oldest_cycles()
+                .is_none_or(|oldest| oldest >= start)
+            {
+                self.travel_to(start);
+                return StopReason::HistoryStart;
+            }
+            end = start;
+        }
+    }
+}
@@ -0,0 +1,463 @@
+use crate::debugger::symbols::{parse_address, parse_number};
+use crate::debugger::{Condition, Debugger, RegisterBreakpoint, StopReason, WatchKind, Watchpoint};
+use crate::emulator::memory::AccessKind;
+use crate::emulator::mnemonics::disassemble;
+use crate::emulator::trace::Register;
+use std::io::{self, BufRead, Write};
+
+/* Instructions `continue` runs before giving control back, so a ROM without breakpoints can't hang the prompt */
+pub const DEFAULT_CONTINUE_LIMIT: u64 = 1_000_000;
+const DEFAULT_DUMP_LEN: usize = 64;
+const DEFAULT_DISASSEMBLY_LEN: u16 = 10;
+
+const HELP: &str = "\
+Commands:
+  s, step [N]           execute N instructions (default 1)
+  n, next               step over a CALL
+  o, out                run until the current subroutine returns
+  c, continue [N]       run until a breakpoint, at most N instructions
+  u, until ADDR         run until PC reaches ADDR
+  sb, stepback [N]      undo N instructions (default 1)
+  rc, reverse-continue  run backwards to the previous breakpoint hit
+  b, break ADDR         set a breakpoint
+  d, delete [ADDR]      remove a breakpoint, or all of them
+  w, watch ADDR[-END] [r|w|rw]
+                        stop when RAM in the range is read and/or written (default w)
+  when REG [OP VALUE]   stop when V0-VF, I, SP, DT or ST changes, optionally
+                        only to a value meeting OP: == != < <= > >=
+  dw N, dr N            remove watchpoint or register breakpoint number N
+  bl, breakpoints       list breakpoints, watchpoints and register breakpoints
+  r, regs               show registers
+  set REG VALUE         set V0-VF, I, PC, DT or ST
+  m, mem ADDR [LEN]     dump memory
+  dis [ADDR] [N]        disassemble N instructions from ADDR (default PC)
+  stack                 show the call stack
+  screen                draw the display as text
+  reset                 reset the machine and reload the ROM
+  h, help               show this help
+  q, quit               leave the debugger
+Addresses and values are decimal, or hexadecimal with a 0x prefix.";
+
+/* Terminal front end of the debugger, reads commands from any BufRead */
+#[derive(Debug)]
+pub struct Repl {
+    debugger: Debugger,
+}
+impl Repl {
+    pub fn new(debugger: Debugger) -> Repl {
+        Repl { debugger }
+    }
+    #[inline]
+    pub fn debugger(&self) -> &Debugger {
+        &self.debugger
+    }
+    #[inline]
+    pub fn debugger_mut(&mut self) -> &mut Debugger {
+        &mut self.debugger
+    }
+    pub fn into_inner(self) -> Debugger {
+        self.debugger
+    }
+    /* Reads commands until quit or end of input; an empty line repeats the last command */
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, mut output: W) -> io::Result<()> {
+        let mut lines = input.lines();
+        let mut last = String::new();
+        self.print_location(&mut output)?;
+        loop {
+            write!(output, "(chip8) ")?;
+            output.flush()?;
+            let Some(line) = lines.next() else {
+                return Ok(());
+            };
+            let line = line?;
+            let line = match line.trim() {
+                "" => last.clone(),
+                command => command.to_string(),
+            };
+            if !self.execute(&line, &mut output)? {
+                return Ok(());
+            }
+            last = line;
+        }
+    }
+    /* Runs a single command, returns false on quit */
+    pub fn execute<W: Write>(&mut self, line: &str, output: &mut W) -> io::Result<bool> {
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else {
+            return Ok(true);
+        };
+        let args: Vec<&str> = words.collect();
+        match self.command(command, &args, output) {
+            Ok(quit) => Ok(quit),
+            Err(CommandError::Io(error)) => Err(error),
+            Err(CommandError::Usage(message)) => {
+                writeln!(output, "{message}")?;
+                Ok(true)
+            }
+        }
+    }
+    fn command<W: Write>(
+        &mut self,
+        command: &str,
+        args: &[&str],
+        output: &mut W,
+    ) -> Result<bool, CommandError> {
+        match command {
+            "s" | "step" => {
+                let count = optional_number(args.first(), 1)?;
+                let mut reason = StopReason::Step;
+                for _ in 0..count {
+                    reason = self.debugger.step();
+                    if !matches!(reason, StopReason::Step) {
+                        break;
+                    }
+                }
+                self.report(reason, output)?;
+            }
+            "n" | "next" => {
+                let reason = self.debugger.step_over();
+                self.report(reason, output)?;
+            }
+            "o" | "out" | "finish" => {
+                let reason = self.debugger.step_out();
+                self.report(reason, output)?;
+            }
+            "c" | "continue" => {
+                let limit = optional_number(args.first(), DEFAULT_CONTINUE_LIMIT)?;
+                let reason = self.debugger.resume(Some(limit));
+                self.report(reason, output)?;
+            }
+            "u" | "until" => {
+                let addr = required_address(args.first())?;
+                let reason = self.debugger.run_to(addr, Some(DEFAULT_CONTINUE_LIMIT));
+                self.report(reason, output)?;
+            }
+            "sb" | "stepback" => {
+                let count = optional_number(args.first(), 1)?;
+                let mut reason = StopReason::Step;
+                for _ in 0..count {
+                    reason = self.debugger.step_back();
+                    if !matches!(reason, StopReason::Step) {
+                        break;
+                    }
+                }
+                self.report(reason, output)?;
+            }
+            "rc" | "reverse-continue" => {
+                let reason = self.debugger.reverse_continue();
+                self.report(reason, output)?;
+            }
+            "b" | "break" => {
+                let addr = required_address(args.first())?;
+                if self.debugger.add_breakpoint(addr) {
+                    writeln!(output, "Breakpoint at {addr:#06x}")?;
+                } else {
+                    writeln!(output, "Breakpoint at {addr:#06x} already set")?;
+                }
+            }
+            "d" | "delete" => match args.first() {
+                Some(_) => {
+                    let addr = required_address(args.first())?;
+                    if !self.debugger.remove_breakpoint(addr) {
+                        writeln!(output, "No breakpoint at {addr:#06x}")?;
+                    }
+                }
+                None => self.debugger.clear_breakpoints(),
+            },
+            "w" | "watch" => {
+                let (start, end) = address_range(args.first())?;
+                let kind = match args.get(1).copied() {
+                    None | Some("w") => WatchKind::Write,
+                    Some("r") => WatchKind::Read,
+                    Some("rw") => WatchKind::ReadWrite,
+                    Some(_) => return Err(CommandError::usage("access must be r, w or rw")),
+                };
+                let watchpoint = Watchpoint::new(start, end, kind);
+                self.debugger.add_watchpoint(watchpoint);
+                writeln!(
+                    output,
+                    "Watchpoint {}: {watchpoint}",
+                    self.debugger.watchpoints().len() - 1
+                )?;
+            }
+            "when" => {
+                let Some(register) = args.first() else {
+                    return Err(CommandError::usage("usage: when REG [OP VALUE]"));
+                };
+                let register: Register = register.parse().map_err(CommandError::Usage)?;
+                let condition = match (args.get(1), args.get(2)) {
+                    (None, _) => Condition::Changed,
+                    (Some(op), Some(value)) => {
+                        let value = u16::try_from(parse_number(value)?)
+                            .map_err(|_| CommandError::usage("value out of range"))?;
+                        Condition::from_operator(op, value)
+                            .ok_or_else(|| CommandError::usage("operators: == != < <= > >="))?
+                    }
+                    (Some(_), None) => {
+                        return Err(CommandError::usage("usage: when REG [OP VALUE]"));
+                    }
+                };
+                let breakpoint = RegisterBreakpoint::new(register, condition);
+                self.debugger.add_register_breakpoint(breakpoint);
+                writeln!(
+                    output,
+                    "Register breakpoint {}: {breakpoint}",
+                    self.debugger.register_breakpoints().len() - 1
+                )?;
+            }
+            "dw" => {
+                let index = parse_number(
+                    args.first()
+                        .ok_or_else(|| CommandError::usage("usage: dw N"))?,
+                )?;
+                if self.debugger.remove_watchpoint(index as usize).is_none() {
+                    writeln!(output, "No watchpoint {index}")?;
+                }
+            }
+            "dr" => {
+                let index = parse_number(
+                    args.first()
+                        .ok_or_else(|| CommandError::usage("usage: dr N"))?,
+                )?;
+                if self
+                    .debugger
+                    .remove_register_breakpoint(index as usize)
+                    .is_none()
+                {
+                    writeln!(output, "No register breakpoint {index}")?;
+                }
+            }
+            "bl" | "breakpoints" => {
+                if self.debugger.breakpoints().next().is_none()
+                    && self.debugger.watchpoints().is_empty()
+                    && self.debugger.register_breakpoints().is_empty()
+                {
+                    writeln!(output, "No breakpoints")?;
+                }
+                for addr in self.debugger.breakpoints() {
+                    writeln!(output, "  {addr:#06x}  {}", self.disassemble_at(addr))?;
+                }
+                for (index, watchpoint) in self.debugger.watchpoints().iter().enumerate() {
+                    writeln!(output, "  watch {index}: {watchpoint}")?;
+                }
+                for (index, breakpoint) in self.debugger.register_breakpoints().iter().enumerate() {
+                    writeln!(output, "  when {index}: {breakpoint}")?;
+                }
+            }
+            "r" | "regs" => self.print_registers(output)?,
+            "set" => {
+                let (Some(register), Some(_)) = (args.first(), args.get(1)) else {
+                    return Err(CommandError::usage("usage: set REG VALUE"));
+                };
+                let value = parse_number(args[1])?;
+                self.set_register(register, value)?;
+            }
+            "m" | "mem" => {
+                let addr = required_address(args.first())?;
+                let len = optional_number(args.get(1), DEFAULT_DUMP_LEN as u64)? as usize;
+                let bytes = self.debugger.read_memory(addr, len);
+                for (row, chunk) in bytes.chunks(16).enumerate() {
+                    let hex: Vec<String> = chunk.iter().map(|b| format!("{b:02X}")).collect();
+                    writeln!(
+                        output,
+                        "  {:#06x}  {}",
+                        addr as usize + row * 16,
+                        hex.join(" ")
+                    )?;
+                }
+            }
+            "dis" => {
+                let start = match args.first() {
+                    Some(_) => required_address(args.first())?,
+                    None => self.debugger.pc(),
+                };
+                let count = optional_number(args.get(1), DEFAULT_DISASSEMBLY_LEN as u64)? as u16;
+                for i in 0..count {
+                    let addr = start.wrapping_add(2 * i);
+                    let marker = if addr == self.debugger.pc() { '>' } else { ' ' };
+                    let bp = if self.debugger.has_breakpoint(addr) {
+                        '*'
+                    } else {
+                        ' '
+                    };
+                    writeln!(
+                        output,
+                        "{marker}{bp} {addr:#06x}  {}",
+                        self.disassemble_at(addr)
+                    )?;
+                }
+            }
+            "stack" => {
+                let stack = &self.debugger.chip8().memory().STACK;
+                if stack.is_empty() {
+                    writeln!(output, "Stack empty")?;
+                }
+                for (depth, addr) in stack.iter().rev().enumerate() {
+                    writeln!(output, "  #{depth:<2} {addr:#06x}")?;
+                }
+            }
+            "screen" => self.print_screen(output)?,
+            "reset" => {
+                self.debugger.reset();
+                self.print_location(output)?;
+            }
+            "h" | "help" | "?" => writeln!(output, "{HELP}")?,
+            "q" | "quit" | "exit" => return Ok(false),
+            _ => {
+                return Err(CommandError::Usage(format!(
+                    "Unknown command '{command}', try 'help'"
+                )));
+            }
+        }
+        Ok(true)
+    }
+    fn set_register(&mut self, register: &str, value: u64) -> Result<(), CommandError> {
+        let cpu = self.debugger.chip8_mut().cpu_mut();
+        let register = register.to_ascii_uppercase();
+        let byte = || u8::try_from(value).map_err(|_| CommandError::usage("value out of range"));
+        let word = || u16::try_from(value).map_err(|_| CommandError::usage("value out of range"));
+        match register.as_str() {
+            "I" => cpu.I = word()?,
+            "PC" => cpu.PROGRAM_COUNTER = word()?,
+            "DT" => cpu.D_TIMER = byte()?,
+            "ST" => cpu.S_TIMER = byte()?,
+            _ => match register
+                .strip_prefix('V')
+                .and_then(|x| u8::from_str_radix(x, 16).ok())
+                .filter(|&x| x < 16)
+            {
+                Some(x) => cpu.V[x as usize] = byte()?,
+                None => return Err(CommandError::usage("registers: V0-VF, I, PC, DT, ST")),
+            },
+        }
+        Ok(())
+    }
+    fn disassemble_at(&self, addr: u16) -> String {
+        match *self.debugger.read_memory(addr, 2) {
+            [hi, lo] => {
+                let opcode = u16::from_be_bytes([hi, lo]);
+                format!("{opcode:04X}  {}", disassemble(opcode))
+            }
+            _ => "outside RAM".to_string(),
+        }
+    }
+    fn print_location<W: Write>(&self, output: &mut W) -> io::Result<()> {
+        let pc = self.debugger.pc();
+        writeln!(output, "{pc:#06x}  {}", self.disassemble_at(pc))
+    }
+    fn print_registers<W: Write>(&self, output: &mut W) -> io::Result<()> {
+        let cpu = self.debugger.chip8().cpu();
+        for (i, regs) in cpu.V.chunks(8).enumerate() {
+            let line: Vec<String> = regs
+                .iter()
+                .enumerate()
+                .map(|(j, v)| format!("V{:X}={:02X}", i * 8 + j, v))
+                .collect();
+            writeln!(output, "  {}", line.join(" "))?;
+        }
+        writeln!(
+            output,
+            "  I={:04X} PC={:04X} SP={:02X} DT={:02X} ST={:02X}  cycle {}",
+            cpu.I,
+            cpu.PROGRAM_COUNTER,
+            self.debugger.call_depth(),
+            cpu.D_TIMER,
+            cpu.S_TIMER,
+            self.debugger.chip8().cycles()
+        )
+    }
+    fn print_screen<W: Write>(&self, output: &mut W) -> io::Result<()> {
+        let display = self.debugger.chip8().display();
+        let scale = display.buffer_width() / display.width();
+        for y in (0..display.buffer_height()).step_by(scale) {
+            let row: String = (0..display.buffer_width())
+                .step_by(scale)
+                .map(|x| {
+                    if display.is_pixel_on(y * display.buffer_width() + x) {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            writeln!(output, "{row}")?;
+        }
+        Ok(())
+    }
+    fn report<W: Write>(&self, reason: StopReason, output: &mut W) -> io::Result<()> {
+        match reason {
+            StopReason::Step => {}
+            StopReason::Breakpoint(addr) => writeln!(output, "Breakpoint at {addr:#06x}")?,
+            StopReason::Watchpoint {
+                pc,
+                watchpoint,
+                access,
+            } => {
+                let verb = match access.kind {
+                    AccessKind::Read => "read",
+                    AccessKind::Write => "wrote",
+                };
+                writeln!(
+                    output,
+                    "Watchpoint {watchpoint}: {pc:#06x} {verb} {:02X} at {:#06x}",
+                    access.value, access.addr
+                )?;
+            }
+            StopReason::RegisterChanged {
+                pc,
+                register,
+                before,
+                after,
+            } => writeln!(
+                output,
+                "{register} changed {before:#x} -> {after:#x} after {pc:#06x}"
+            )?,
+            StopReason::Reached(_) => {}
+            StopReason::Exited => writeln!(output, "Program exited")?,
+            StopReason::Limit => writeln!(output, "Paused after the instruction limit")?,
+            StopReason::Fault(error) => writeln!(output, "Halted: {error}")?,
+            StopReason::HistoryStart => writeln!(output, "Reached the oldest recorded state")?,
+        }
+        self.print_location(output)
+    }
+}
+
+enum CommandError {
+    Io(io::Error),
+    Usage(String),
+}
+impl CommandError {
+    fn usage(message: &str) -> CommandError {
+        CommandError::Usage(message.to_string())
+    }
+}
+impl From<String> for CommandError {
+    fn from(message: String) -> CommandError {
+        CommandError::Usage(message)
+    }
+}
+impl From<io::Error> for CommandError {
+    fn from(error: io::Error) -> CommandError {
+        CommandError::Io(error)
+    }
+}
+
+fn optional_number(s: Option<&&str>, default: u64) -> Result<u64, CommandError> {
+    Ok(s.map_or(Ok(default), |s| parse_number(s))?)
+}
+/* Parses ADDR or START-END */
+fn address_range(s: Option<&&str>) -> Result<(u16, u16), CommandError> {
+    let s = s.ok_or_else(|| CommandError::usage("missing address"))?;
+    match s.split_once('-') {
+        Some((start, end)) => Ok((parse_address(start)?, parse_address(end)?)),
+        None => {
+            let addr = parse_address(s)?;
+            Ok((addr, addr))
+        }
+    }
+}
+/* Parses a required address argument */
+fn required_address(s: Option<&&str>) -> Result<u16, CommandError> {
+    let s = s.ok_or_else(|| CommandError::usage("missing address"))?;
+    Ok(parse_address(s)?)
+}
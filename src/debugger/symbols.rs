@@ -0,0 +1,103 @@
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/* Maps Octo source lines and labels to ROM addresses, e.g. {"source": "game.8o", "lines": {"12": "0x202"}, "labels": {"main": 512}} */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SymbolMap {
+    /* Source file the lines refer to, relative paths are relative to the map */
+    pub source: Option<PathBuf>,
+    pub lines: BTreeMap<u32, u16>,
+    pub labels: BTreeMap<String, u16>,
+}
+impl SymbolMap {
+    pub fn new() -> SymbolMap {
+        SymbolMap {
+            ..Default::default()
+        }
+    }
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<SymbolMap> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        let mut map = SymbolMap::parse(&text)
+            .map_err(|message| io::Error::new(io::ErrorKind::InvalidData, message))?;
+        if let Some(source) = &map.source
+            && source.is_relative()
+            && let Some(dir) = path.parent()
+        {
+            map.source = Some(dir.join(source));
+        }
+        Ok(map)
+    }
+    pub fn parse(text: &str) -> Result<SymbolMap, String> {
+        let json: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        let mut map = SymbolMap::new();
+        map.source = json["source"].as_str().map(PathBuf::from);
+        if let Some(lines) = json["lines"].as_object() {
+            for (line, addr) in lines {
+                let line = line
+                    .parse()
+                    .map_err(|_| format!("invalid line number '{line}'"))?;
+                map.lines.insert(line, json_address(addr)?);
+            }
+        }
+        if let Some(labels) = json["labels"].as_object() {
+            for (label, addr) in labels {
+                map.labels.insert(label.clone(), json_address(addr)?);
+            }
+        }
+        Ok(map)
+    }
+    /* Address of the first instruction on or after `line`, with the line it was found on */
+    pub fn address_of_line(&self, line: u32) -> Option<(u32, u16)> {
+        self.lines
+            .range(line..)
+            .next()
+            .map(|(&line, &addr)| (line, addr))
+    }
+    /* Source line of the instruction at addr */
+    pub fn line_of(&self, addr: u16) -> Option<u32> {
+        self.lines
+            .iter()
+            .find(|&(_, &a)| a == addr)
+            .map(|(&line, _)| line)
+    }
+    pub fn label(&self, name: &str) -> Option<u16> {
+        self.labels.get(name).copied()
+    }
+    /* Closest label at or before addr, with the offset from it */
+    pub fn label_before(&self, addr: u16) -> Option<(&str, u16)> {
+        self.labels
+            .iter()
+            .filter(|&(_, &a)| a <= addr)
+            .max_by_key(|&(_, &a)| a)
+            .map(|(name, &a)| (name.as_str(), addr - a))
+    }
+}
+
+/* Parses a number, hexadecimal with a 0x prefix or decimal */
+pub fn parse_number(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let parsed = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => s.parse(),
+    };
+    parsed.map_err(|e| format!("invalid number '{s}': {e}"))
+}
+/* Parses an address like parse_number, used by the command line, the REPL and the DAP server */
+pub fn parse_address(s: &str) -> Result<u16, String> {
+    let s = s.trim();
+    let addr = parse_number(s).map_err(|_| format!("invalid address '{s}'"))?;
+    u16::try_from(addr).map_err(|_| format!("address '{s}' out of range"))
+}
+
+/* Parses a JSON number or "0x" prefixed hexadecimal string as an address */
+fn json_address(value: &Value) -> Result<u16, String> {
+    match value {
+        Value::Number(n) => n.as_u64().and_then(|addr| u16::try_from(addr).ok()),
+        Value::String(s) => parse_address(s).ok(),
+        _ => None,
+    }
+    .ok_or_else(|| format!("invalid address {value}"))
+}
@@ -0,0 +1,133 @@
+use crate::emulator::memory::{AccessKind, MemoryAccess};
+use crate::emulator::trace::Register;
+use std::fmt;
+
+/* Which RAM accesses trigger a watchpoint */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchKind {
+    Read,
+    Write,
+    ReadWrite,
+}
+impl WatchKind {
+    pub fn matches(&self, kind: AccessKind) -> bool {
+        match self {
+            WatchKind::Read => kind == AccessKind::Read,
+            WatchKind::Write => kind == AccessKind::Write,
+            WatchKind::ReadWrite => true,
+        }
+    }
+}
+impl fmt::Display for WatchKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            WatchKind::Read => "read",
+            WatchKind::Write => "write",
+            WatchKind::ReadWrite => "read/write",
+        })
+    }
+}
+
+/* Stops when an instruction reads or writes RAM in start..=end */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Watchpoint {
+    pub start: u16,
+    pub end: u16,
+    pub kind: WatchKind,
+}
+impl Watchpoint {
+    pub fn new(start: u16, end: u16, kind: WatchKind) -> Watchpoint {
+        Watchpoint {
+            start: start.min(end),
+            end: start.max(end),
+            kind,
+        }
+    }
+    pub fn matches(&self, access: &MemoryAccess) -> bool {
+        self.kind.matches(access.kind)
+            && (self.start as usize..=self.end as usize).contains(&access.addr)
+    }
+}
+impl fmt::Display for Watchpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.start == self.end {
+            write!(f, "{} {:#06x}", self.kind, self.start)
+        } else {
+            write!(f, "{} {:#06x}-{:#06x}", self.kind, self.start, self.end)
+        }
+    }
+}
+
+/* Test applied to a register's new value */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Condition {
+    /* Any change */
+    Changed,
+    Equal(u16),
+    NotEqual(u16),
+    Less(u16),
+    LessEqual(u16),
+    Greater(u16),
+    GreaterEqual(u16),
+}
+impl Condition {
+    /* Builds a condition from an operator such as "==" and a value */
+    pub fn from_operator(op: &str, value: u16) -> Option<Condition> {
+        Some(match op {
+            "==" | "=" => Condition::Equal(value),
+            "!=" => Condition::NotEqual(value),
+            "<" => Condition::Less(value),
+            "<=" => Condition::LessEqual(value),
+            ">" => Condition::Greater(value),
+            ">=" => Condition::GreaterEqual(value),
+            _ => return None,
+        })
+    }
+    pub fn holds(&self, value: u16) -> bool {
+        match *self {
+            Condition::Changed => true,
+            Condition::Equal(v) => value == v,
+            Condition::NotEqual(v) => value != v,
+            Condition::Less(v) => value < v,
+            Condition::LessEqual(v) => value <= v,
+            Condition::Greater(v) => value > v,
+            Condition::GreaterEqual(v) => value >= v,
+        }
+    }
+}
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Condition::Changed => write!(f, "changes"),
+            Condition::Equal(v) => write!(f, "== {v:#x}"),
+            Condition::NotEqual(v) => write!(f, "!= {v:#x}"),
+            Condition::Less(v) => write!(f, "< {v:#x}"),
+            Condition::LessEqual(v) => write!(f, "<= {v:#x}"),
+            Condition::Greater(v) => write!(f, "> {v:#x}"),
+            Condition::GreaterEqual(v) => write!(f, ">= {v:#x}"),
+        }
+    }
+}
+
+/* Stops when an instruction changes a register and its new value meets the condition */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegisterBreakpoint {
+    pub register: Register,
+    pub condition: Condition,
+}
+impl RegisterBreakpoint {
+    pub fn new(register: Register, condition: Condition) -> RegisterBreakpoint {
+        RegisterBreakpoint {
+            register,
+            condition,
+        }
+    }
+    pub fn matches(&self, before: u16, after: u16) -> bool {
+        before != after && self.condition.holds(after)
+    }
+}
+impl fmt::Display for RegisterBreakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.register, self.condition)
+    }
+}
@@ -1,15 +1,27 @@
-use crate::emulator::Audio;
-use crate::emulator::display::{Display, WINDOW_HEIGHT, WINDOW_WIDTH};
-use crate::emulator::errors::OpcodeError;
-use crate::emulator::keyboard::Keypad;
-use crate::emulator::memory::{FONT_BASE_ADDR, Memory, START_ADDR};
+use crate::emulator::display::{Display, NUM_PLANES};
+use crate::emulator::errors::{CpuError, ErrorPolicy, KeyboardError, MemoryError, OpcodeError};
+use crate::emulator::keyboard::{Keypad, NUM_KEYS};
+use crate::emulator::memory::{
+    AccessKind, BIG_FONT_BASE_ADDR, FONT_BASE_ADDR, Memory, MemoryAccess, START_ADDR,
+};
 use crate::emulator::mnemonics::Mnemonics;
+use crate::emulator::platform::InstructionSet;
+use crate::emulator::quirks::Quirks;
+use crate::emulator::rng::Rng;
 
 const GLYPH_BYTES: usize = 5;
+const BIG_GLYPH_BYTES: usize = 10;
 const NUM_V_REGS: usize = 16;
+/* RPL user flags, 8 on the HP48 (SUPER-CHIP), 16 on XO-CHIP */
+const NUM_RPL_FLAGS: usize = 16;
+const SCHIP_RPL_FLAGS: usize = 8;
+/* XO-CHIP audio pattern, 128 1-bit samples */
+pub const AUDIO_PATTERN_BYTES: usize = 16;
+/* XO-CHIP pitch giving a 4000Hz sample rate */
+pub const DEFAULT_PITCH: u8 = 64;
 
 #[allow(non_snake_case)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CPU {
     pub V: [u8; NUM_V_REGS],
     pub I: u16,
@@ -17,6 +29,23 @@ pub struct CPU {
     pub D_TIMER: u8,
     pub PROGRAM_COUNTER: u16,
     pub STACK_POINTER: u8,
+    /* RPL user flags (FX75/FX85), kept across resets like on the HP48 */
+    pub RPL: [u8; NUM_RPL_FLAGS],
+    /* XO-CHIP audio pattern (F002), None until a ROM loads one, and pitch (FX3A) */
+    pub AUDIO_PATTERN: Option<[u8; AUDIO_PATTERN_BYTES]>,
+    pub PITCH: u8,
+    /* Opcode behaviours, opcode set and error handling, kept across resets */
+    pub quirks: Quirks,
+    pub instruction_set: InstructionSet,
+    pub error_policy: ErrorPolicy,
+    /* Set by 00FD, the program asked the interpreter to exit */
+    pub exited: bool,
+    /* Set by DXYN when the display wait quirk is on, the frame ends early */
+    pub vblank_wait: bool,
+    /* CXNN random number generator */
+    pub rng: Rng,
+    /* Data reads and writes of RAM by the last instructions, None when not observed, kept across resets */
+    pub access_log: Option<Vec<MemoryAccess>>,
 }
 impl Default for CPU {
     fn default() -> CPU {
@@ -27,6 +56,16 @@ impl Default for CPU {
             D_TIMER: 0,
             PROGRAM_COUNTER: START_ADDR as u16,
             STACK_POINTER: 0,
+            RPL: [0; NUM_RPL_FLAGS],
+            AUDIO_PATTERN: None,
+            PITCH: DEFAULT_PITCH,
+            quirks: Quirks::default(),
+            instruction_set: InstructionSet::Chip8,
+            error_policy: ErrorPolicy::Halt,
+            exited: false,
+            vblank_wait: false,
+            rng: Rng::new(),
+            access_log: None,
         }
     }
 }
@@ -37,31 +76,130 @@ impl CPU {
             ..Default::default()
         }
     }
+    pub fn with_quirks(quirks: Quirks) -> CPU {
+        CPU {
+            quirks,
+            ..Default::default()
+        }
+    }
     #[inline]
     pub fn reset(&mut self) {
-        *self = CPU::default();
+        *self = CPU {
+            RPL: self.RPL,
+            quirks: self.quirks,
+            instruction_set: self.instruction_set,
+            error_policy: self.error_policy,
+            rng: self.rng,
+            access_log: self.access_log.take(),
+            ..Default::default()
+        };
     }
-    pub fn update_timers(&mut self, audio: &mut Audio) {
+    pub fn update_timers(&mut self) {
         if self.D_TIMER > 0 {
             self.D_TIMER -= 1;
         }
         if self.S_TIMER > 0 {
-            audio.play_beep();
-
             self.S_TIMER -= 1;
-        } else {
-            audio.stop_beep();
         }
     }
     #[inline]
+    /* Returns if the beeper should be sounding */
+    pub fn sound_active(&self) -> bool {
+        self.S_TIMER > 0
+    }
+    #[inline]
     fn increment_PC(&mut self) {
-        self.PROGRAM_COUNTER += 2
+        self.PROGRAM_COUNTER = self.PROGRAM_COUNTER.wrapping_add(2)
+    }
+    #[inline]
+    /* Out of range addresses wrap around RAM under the wrap error policy */
+    fn wrap_addr(&self, memory: &Memory, addr: usize) -> usize {
+        match self.error_policy {
+            ErrorPolicy::Wrap => addr % memory.RAM.len(),
+            ErrorPolicy::Halt | ErrorPolicy::Ignore => addr,
+        }
+    }
+    /* Reads RAM, applying the error policy to out of range addresses, without observing it */
+    fn read_ram(&self, memory: &Memory, addr: usize) -> Result<Option<u8>, MemoryError> {
+        match memory.read(self.wrap_addr(memory, addr)) {
+            Ok(value) => Ok(Some(value)),
+            Err(_) if self.error_policy == ErrorPolicy::Ignore => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+    /* Reads a byte of the instruction stream, fetches are not recorded in the access log */
+    fn load_opcode(&self, memory: &Memory, addr: usize) -> Result<u8, MemoryError> {
+        Ok(self.read_ram(memory, addr)?.unwrap_or(0))
+    }
+    /* Reads RAM as data, every access of an instruction goes through here or store */
+    fn load(&mut self, memory: &Memory, addr: usize) -> Result<u8, MemoryError> {
+        let value = self.read_ram(memory, addr)?;
+        if let Some(value) = value {
+            self.observe(AccessKind::Read, self.wrap_addr(memory, addr), value);
+        }
+        Ok(value.unwrap_or(0))
     }
-    pub fn fetch(&mut self, memory: &Memory) -> u16 {
-        let opcode = ((memory.RAM[self.PROGRAM_COUNTER as usize] as u16) << 8)
-            | (memory.RAM[self.PROGRAM_COUNTER as usize + 1] as u16);
+    /* Writes RAM, applying the error policy to out of range addresses */
+    fn store(&mut self, memory: &mut Memory, addr: usize, value: u8) -> Result<(), MemoryError> {
+        let addr = self.wrap_addr(memory, addr);
+        match memory.write(addr, value) {
+            Ok(()) => self.observe(AccessKind::Write, addr, value),
+            Err(_) if self.error_policy == ErrorPolicy::Ignore => {}
+            Err(error) => return Err(error),
+        }
+        Ok(())
+    }
+    #[inline]
+    /* Records a data access when the access log is enabled */
+    fn observe(&mut self, kind: AccessKind, addr: usize, value: u8) {
+        if let Some(log) = &mut self.access_log {
+            log.push(MemoryAccess { kind, addr, value });
+        }
+    }
+    /* Returns the error, unless the error policy says to carry on */
+    fn fault<E: Into<CpuError>>(&self, error: E) -> Result<(), CpuError> {
+        match self.error_policy {
+            ErrorPolicy::Halt => Err(error.into()),
+            ErrorPolicy::Wrap | ErrorPolicy::Ignore => Ok(()),
+        }
+    }
+    /* Whether the key in V[x] is down, keys above F fault or wrap to their low nibble */
+    fn key_down(&self, keyboard: &Keypad, x: u8) -> Result<bool, CpuError> {
+        let key = self.V[x as usize];
+        if (key as usize) < NUM_KEYS {
+            return Ok(keyboard.is_key_down(key as usize));
+        }
+        self.fault(KeyboardError::KeyOutOfRange(key))?;
+        Ok(self.error_policy == ErrorPolicy::Wrap && keyboard.is_key_down((key & 0xF) as usize))
+    }
+    /* Skips the next instruction, the 4 byte F000 NNNN counts as one on XO-CHIP */
+    fn skip(&mut self, memory: &Memory) {
+        let pc = self.PROGRAM_COUNTER as usize;
+        if self.instruction_set >= InstructionSet::XoChip
+            && memory.RAM.get(pc) == Some(&0xF0)
+            && memory.RAM.get(pc + 1) == Some(&0x00)
+        {
+            self.increment_PC();
+        }
         self.increment_PC();
-        opcode
+    }
+    #[inline]
+    /* Returns the number of RPL user flags available to FX75/FX85 */
+    fn rpl_flags(&self) -> usize {
+        if self.instruction_set >= InstructionSet::XoChip {
+            NUM_RPL_FLAGS
+        } else {
+            SCHIP_RPL_FLAGS
+        }
+    }
+    pub fn fetch(&mut self, memory: &Memory) -> Result<u16, MemoryError> {
+        let pc = self.PROGRAM_COUNTER as usize;
+        let opcode = u16::from_be_bytes([
+            self.load_opcode(memory, pc)?,
+            self.load_opcode(memory, pc + 1)?,
+        ]);
+        self.increment_PC();
+        Ok(opcode)
     }
     pub fn decode_execute(
         &mut self,
@@ -69,46 +207,98 @@ impl CPU {
         display: &mut Display,
         keyboard: &mut Keypad,
         instr: u16,
-    ) -> Result<(), OpcodeError> {
+    ) -> Result<(), CpuError> {
         /* Decode & Execute */
-        let mnemonic = Mnemonics::try_from(instr)?;
-        println!("{:?}", mnemonic);
-        let _ = match mnemonic {
+        let mnemonic = match Mnemonics::try_from(instr) {
+            Ok(mnemonic) => mnemonic,
+            Err(error) => return self.fault(error),
+        };
+        /* Opcodes from extensions the platform doesn't have are unknown */
+        if mnemonic.instruction_set() > self.instruction_set {
+            return self.fault(OpcodeError::UnknownMnemonic(instr));
+        }
+        match mnemonic {
+            /* 00CN - Scroll display N pixels down */
+            Mnemonics::SCROLL_DOWN { n } => {
+                display.scroll_down(n as usize);
+            }
             /* 00E0 - Clear the Display  */
             Mnemonics::CLEAR => {
-                let _ = &display.clear();
+                display.clear();
+            }
+            /* 00DN - Scroll display N pixels up */
+            Mnemonics::SCROLL_UP { n } => {
+                display.scroll_up(n as usize);
             }
             Mnemonics::RETURN => {
                 /* 00EE - Returns from subroutine. PC = Address popped from STACK */
-                self.PROGRAM_COUNTER = memory.stack_pop().unwrap();
+                match memory.stack_pop() {
+                    Ok(addr) => self.PROGRAM_COUNTER = addr,
+                    Err(error) => self.fault(error)?,
+                }
                 self.STACK_POINTER = memory.stack_len();
             }
+            /* 00FB - Scroll display 4 pixels right */
+            Mnemonics::SCROLL_RIGHT => {
+                display.scroll_right(4);
+            }
+            /* 00FC - Scroll display 4 pixels left */
+            Mnemonics::SCROLL_LEFT => {
+                display.scroll_left(4);
+            }
+            Mnemonics::EXIT => {
+                /* 00FD - Exit interpreter, PC stays on this instruction */
+                self.PROGRAM_COUNTER = self.PROGRAM_COUNTER.wrapping_sub(2);
+                self.exited = true;
+            }
+            /* 00FE - Low resolution mode */
+            Mnemonics::LORES => {
+                display.set_hires(false);
+            }
+            /* 00FF - High resolution mode */
+            Mnemonics::HIRES => {
+                display.set_hires(true);
+            }
             Mnemonics::JUMP { nnn } => {
                 /* 1NNN - Jump (goto) address NNN */
                 self.PROGRAM_COUNTER = nnn
             }
             Mnemonics::CALL { nnn } => {
                 /* 2NNN - Call subroutine at address NNN. Push current PC Address to STACK, then PC = NNN */
-                memory.stack_push(self.PROGRAM_COUNTER).unwrap();
+                match memory.stack_push(self.PROGRAM_COUNTER) {
+                    Ok(_) => self.PROGRAM_COUNTER = nnn,
+                    Err(error) => self.fault(error)?,
+                }
                 self.STACK_POINTER = memory.stack_len();
-                self.PROGRAM_COUNTER = nnn
             }
             Mnemonics::SE_Vx_NN { x, nn } => {
                 /* 3XNN - Skip next instruction (PC += 2), if V[x] == NN */
                 if self.V[x as usize] == nn {
-                    self.increment_PC();
+                    self.skip(memory);
                 }
             }
             Mnemonics::SNE_Vx_NN { x, nn } => {
                 /* 4XNN - Skip next instruction (PC += 2), if V[x] != NN */
                 if self.V[x as usize] != nn {
-                    self.increment_PC();
+                    self.skip(memory);
                 }
             }
             Mnemonics::SE_Vx_Vy { x, y } => {
                 /* 5XY0 - Skip next instruction (PC += 2), if V[x] != V[Y] */
                 if self.V[x as usize] == self.V[y as usize] {
-                    self.increment_PC();
+                    self.skip(memory);
+                }
+            }
+            Mnemonics::STORE_RANGE_Vx_Vy { x, y } => {
+                /* 5XY2 - RAM[I] .. = V[x] .. V[y], in either direction, [I] unchanged */
+                for (offset, reg) in register_range(x, y).enumerate() {
+                    self.store(memory, self.I as usize + offset, self.V[reg])?;
+                }
+            }
+            Mnemonics::LOAD_RANGE_Vx_Vy { x, y } => {
+                /* 5XY3 - V[x] .. V[y] = RAM[I] .., in either direction, [I] unchanged */
+                for (offset, reg) in register_range(x, y).enumerate() {
+                    self.V[reg] = self.load(memory, self.I as usize + offset)?;
                 }
             }
             Mnemonics::LOAD_Vx_NN { x, nn } => {
@@ -125,15 +315,24 @@ impl CPU {
             }
             Mnemonics::OR_Vx_Vy { x, y } => {
                 /* 8XY1 - V[x] = V[x] |(OR) V[y] */
-                self.V[x as usize] = self.V[x as usize] | self.V[y as usize];
+                self.V[x as usize] |= self.V[y as usize];
+                if self.quirks.vf_reset {
+                    self.V[0xF] = 0;
+                }
             }
             Mnemonics::AND_Vx_Vy { x, y } => {
                 /* 8XY2 - V[x] = V[x] &(AND) V[y] */
-                self.V[x as usize] = self.V[x as usize] & self.V[y as usize];
+                self.V[x as usize] &= self.V[y as usize];
+                if self.quirks.vf_reset {
+                    self.V[0xF] = 0;
+                }
             }
             Mnemonics::XOR_Vx_Vy { x, y } => {
                 /* 8XY3 - V[x] = V[x] ^(XOR) V[y] */
-                self.V[x as usize] = self.V[x as usize] ^ self.V[y as usize];
+                self.V[x as usize] ^= self.V[y as usize];
+                if self.quirks.vf_reset {
+                    self.V[0xF] = 0;
+                }
             }
             Mnemonics::ADD_Vx_Vy { x, y } => {
                 /* 8XY4 - V[x] = V[x] + [Vy](sum), set V[0xF] = Carry  */
@@ -144,12 +343,16 @@ impl CPU {
             Mnemonics::SUB_Vx_Vy { x, y } => {
                 /* 8XY5 - V[x] = V[x] - V[y](difference), V[0xF] = (NOT) !borrow */
                 let (difference, borrow) = self.V[x as usize].overflowing_sub(self.V[y as usize]);
-                self.V[0xF] = u8::from(!borrow);
                 self.V[x as usize] = difference;
+                self.V[0xF] = u8::from(!borrow);
             }
             Mnemonics::SHR_Vx_Vy { x, y } => {
                 /* 8XY6 - V[x] = V[y] >> (Shift Right) 1, then V[0xF] = LSB */
-                let value = self.V[y as usize];
+                let value = if self.quirks.shift {
+                    self.V[x as usize]
+                } else {
+                    self.V[y as usize]
+                };
                 let lsb = value & 0x1;
                 self.V[x as usize] = value >> 1;
                 self.V[0xF] = lsb;
@@ -157,12 +360,16 @@ impl CPU {
             Mnemonics::SUBN_Vx_Vy { x, y } => {
                 /* 8XY7 - V[x] = V[y] - V[x](differnce), then V[0xF] = (NOT)!borrow */
                 let (difference, borrow) = self.V[y as usize].overflowing_sub(self.V[x as usize]);
-                self.V[0xF] = u8::from(!borrow);
                 self.V[x as usize] = difference;
+                self.V[0xF] = u8::from(!borrow);
             }
             Mnemonics::SHL_Vx_Vy { x, y } => {
-                /* 8XYE -  V[x] = V[x] << (Shift Left) 1, then V[0xF] = MSB */
-                let value = self.V[y as usize];
+                /* 8XYE -  V[x] = V[y] << (Shift Left) 1, then V[0xF] = MSB */
+                let value = if self.quirks.shift {
+                    self.V[x as usize]
+                } else {
+                    self.V[y as usize]
+                };
                 let msb = (value >> 7) & 0x1;
 
                 self.V[x as usize] = value << 1;
@@ -171,7 +378,7 @@ impl CPU {
             Mnemonics::SNE_Vx_Vy { x, y } => {
                 /* 9XY0 - Skip next instruction (PC += 2) if V[x] (NOT) != V[y] */
                 if self.V[x as usize] != self.V[y as usize] {
-                    self.increment_PC();
+                    self.skip(memory);
                 }
             }
             Mnemonics::LOAD_I_NNN { nnn } => {
@@ -179,62 +386,114 @@ impl CPU {
                 self.I = nnn;
             }
             Mnemonics::JUMP_V0_NNN { nnn } => {
-                /* BNNN - Jump(goto) address V[0x0] + NNN */
-                self.PROGRAM_COUNTER = u16::from(self.V[0x0]) + nnn
+                /* BNNN - Jump(goto) address V[0x0] + NNN, or V[x] + XNN */
+                let offset = if self.quirks.jump_with_vx {
+                    self.V[((nnn >> 8) & 0xF) as usize]
+                } else {
+                    self.V[0x0]
+                };
+                self.PROGRAM_COUNTER = u16::from(offset) + nnn
             }
             Mnemonics::RAND { x, nn } => {
                 /* CXNN - V[x] = (Random u8 Byte) &&(AND) NN */
-                use rand::Rng;
-                let random_byte: u8 = rand::rng().random();
-                self.V[x as usize] = random_byte & nn;
+                self.V[x as usize] = self.rng.next_byte(memory) & nn;
             }
             Mnemonics::DRAW { x, y, n } => {
-                /* DRAW -  Display N sprite, starting at [I] at (V[x], V[y]), then V[0xF] = collision */
+                /* DRAW -  Display N sprite, starting at [I] at (V[x], V[y]), then V[0xF] = collision
+                DXY0 - 16x16 sprite of 2 bytes per row on SUPER-CHIP, 8x16 in lores on SUPER-CHIP 1.0 */
+                let (rows, width) = match self.instruction_set {
+                    InstructionSet::SuperChip10 if n == 0 && !display.is_hires() => (16, 8),
+                    set if n == 0 && set >= InstructionSet::SuperChip10 => (16, 16),
+                    _ => (n as usize, 8),
+                };
+                /* Starting position always wraps, the sprite itself wraps or clips */
+                let x0 = display.get_x_postion(self.V[x as usize] as usize);
+                let y0 = display.get_y_postion(self.V[y as usize] as usize);
+                /* Each selected plane reads its own sprite, one after another in RAM */
+                let sprite_bytes = rows * width / 8;
+                let selected = display.selected_planes();
+                let planes = (0..NUM_PLANES as u8)
+                    .map(|idx| 1 << idx)
+                    .filter(|&plane| selected & plane != 0);
                 self.V[0xF] = 0; // Reset collision
-                for row in 0..(n as usize) {
-                    let sprite_byte = memory.RAM[self.I as usize + row];
-                    for bit in 0..8 {
-                        /* Check if current bit of the sprite is set */
-                        let pixel_on = (sprite_byte & (0x80 >> bit)) != 0;
-                        if !pixel_on {
-                            continue;
+                for (plane_idx, plane) in planes.enumerate() {
+                    let sprite_addr = self.I as usize + plane_idx * sprite_bytes;
+                    for row in 0..rows {
+                        if self.quirks.clipping && y0 + row >= display.height() {
+                            break;
                         }
-                        /* Screen (X and Y) coordinates (idx) */
-                        let idx = (display.get_x_postion(self.V[x as usize] as usize) + bit)
-                            % WINDOW_WIDTH
-                            + (display.get_y_postion(self.V[y as usize] as usize) + row)
-                                % WINDOW_HEIGHT
-                                * WINDOW_WIDTH;
-                        /* If pixel is ON (1), collision detected, set VF = true (1) */
-                        if display.is_pixel_on(idx) {
-                            self.V[0xF] = 1;
+                        let addr = sprite_addr + row * width / 8;
+                        let sprite_row = if width == 16 {
+                            u16::from_be_bytes([
+                                self.load(memory, addr)?,
+                                self.load(memory, addr + 1)?,
+                            ])
+                        } else {
+                            u16::from(self.load(memory, addr)?) << 8
+                        };
+                        for bit in 0..width {
+                            /* Check if current bit of the sprite is set */
+                            let pixel_on = (sprite_row & (0x8000 >> bit)) != 0;
+                            if !pixel_on {
+                                continue;
+                            }
+                            if self.quirks.clipping && x0 + bit >= display.width() {
+                                break;
+                            }
+                            /* Screen (X and Y) coordinates */
+                            let px = (x0 + bit) % display.width();
+                            let py = (y0 + row) % display.height();
+                            /* If pixel is ON(1) flip (XOR) to OFF(0), collision detected, set VF = true (1) */
+                            if display.toggle_pixel(px, py, plane) {
+                                self.V[0xF] = 1;
+                            }
                         }
-                        /* If pixel is ON(1) flip (XOR) to OFF(0) */
-                        display.set_pixels(idx);
                     }
                 }
+                self.vblank_wait = self.quirks.display_wait;
             }
             Mnemonics::SKP_Vx { x } => {
                 /* EX9E - Skip next instruction (PC += 2) if [KEY] == V[x] is pressed */
-                if keyboard.is_key_down(self.V[x as usize] as usize) {
-                    self.increment_PC();
+                if self.key_down(keyboard, x)? {
+                    self.skip(memory);
                 }
             }
             Mnemonics::SKNP_Vx { x } => {
                 /* EXA1 - Skip next instruction (PC += 2) if [KEY] == V[x] is NOT pressed */
-                if !keyboard.is_key_down(self.V[x as usize] as usize) {
-                    self.increment_PC();
+                if !self.key_down(keyboard, x)? {
+                    self.skip(memory);
                 }
             }
+            Mnemonics::LOAD_I_LONG => {
+                /* F000 NNNN - [I] = NNNN, the 16 bit address following the opcode */
+                let pc = self.PROGRAM_COUNTER as usize;
+                self.I = u16::from_be_bytes([
+                    self.load_opcode(memory, pc)?,
+                    self.load_opcode(memory, pc + 1)?,
+                ]);
+                self.increment_PC();
+            }
+            Mnemonics::PLANE { n } => {
+                /* FN01 - Select drawing planes N (bitmask) */
+                display.select_planes(n);
+            }
+            Mnemonics::LOAD_AUDIO => {
+                /* F002 - Audio pattern = RAM[I] .. RAM[I + 15] */
+                let mut pattern = [0; AUDIO_PATTERN_BYTES];
+                for (offset, byte) in pattern.iter_mut().enumerate() {
+                    *byte = self.load(memory, self.I as usize + offset)?;
+                }
+                self.AUDIO_PATTERN = Some(pattern);
+            }
             Mnemonics::LOAD_Vx_DT { x } => {
                 /* FX07 - Load, V[x] = [DELAY_TIMER] */
                 self.V[x as usize] = self.D_TIMER;
             }
             Mnemonics::LOAD_Vx_K { x } => {
-                /* FX0A - Wait.. for [KEY] pressed then V[x] = [KEY] */
-                if let Some(key) = keyboard.get_key_pressed(&display) {
-                    self.V[x as usize] = key as u8;
-                    self.increment_PC();
+                /* FX0A - Wait.. for [KEY] pressed then V[x] = [KEY], PC stays on this instruction until then */
+                match keyboard.get_key_pressed() {
+                    Some(key) => self.V[x as usize] = key as u8,
+                    None => self.PROGRAM_COUNTER = self.PROGRAM_COUNTER.wrapping_sub(2),
                 }
             }
             Mnemonics::LOAD_DT_Vx { x } => {
@@ -254,29 +513,61 @@ impl CPU {
                 let digit = (self.V[x as usize] & 0x0F) as usize;
                 self.I = (FONT_BASE_ADDR + digit * GLYPH_BYTES) as u16;
             }
+            Mnemonics::LOAD_BIG_FONT { x } => {
+                /* FX30 - [I] = address of 8x10 sprite for digit V[x] */
+                let digit = (self.V[x as usize] & 0x0F) as usize;
+                self.I = (BIG_FONT_BASE_ADDR + digit * BIG_GLYPH_BYTES) as u16;
+            }
+            Mnemonics::LOAD_PITCH_Vx { x } => {
+                /* FX3A - [PITCH] = V[x] */
+                self.PITCH = self.V[x as usize];
+            }
             Mnemonics::LOAD_B_Vx { x } => {
                 /* FX33 - Load, [I], [I + 1] and [I + 2]  = V[x] (as Binary) */
-                memory.RAM[self.I as usize] = self.V[x as usize] / 100;
-                memory.RAM[self.I as usize + 1] = (self.V[x as usize] / 10) % 10;
-                memory.RAM[self.I as usize + 2] = self.V[x as usize] % 10;
+                let value = self.V[x as usize];
+                self.store(memory, self.I as usize, value / 100)?;
+                self.store(memory, self.I as usize + 1, (value / 10) % 10)?;
+                self.store(memory, self.I as usize + 2, value % 10)?;
             }
             Mnemonics::LOAD_I_Vx { x } => {
                 /* FX55 - RAM[I] .. RAM[Ix] = V[0x0] .. V[x] */
                 for idx in 0..=x as usize {
-                    memory.RAM[self.I as usize + idx] = self.V[idx];
+                    self.store(memory, self.I as usize + idx, self.V[idx])?;
+                }
+                if self.quirks.load_store_increment {
+                    self.I = self.I.wrapping_add(u16::from(x) + 1);
                 }
             }
             Mnemonics::LOAD_Vx_I { x } => {
                 /* FX65 - V[0x0] .. V[x] = RAM[I] .. RAM[Ix] */
                 for idx in 0..=x as usize {
-                    self.V[idx] = memory.RAM[self.I as usize + idx];
+                    self.V[idx] = self.load(memory, self.I as usize + idx)?;
+                }
+                if self.quirks.load_store_increment {
+                    self.I = self.I.wrapping_add(u16::from(x) + 1);
                 }
             }
+            Mnemonics::LOAD_R_Vx { x } => {
+                /* FX75 - RPL[0x0] .. RPL[x] = V[0x0] .. V[x] */
+                let count = (x as usize + 1).min(self.rpl_flags());
+                self.RPL[..count].copy_from_slice(&self.V[..count]);
+            }
+            Mnemonics::LOAD_Vx_R { x } => {
+                /* FX85 - V[0x0] .. V[x] = RPL[0x0] .. RPL[x] */
+                let count = (x as usize + 1).min(self.rpl_flags());
+                self.V[..count].copy_from_slice(&self.RPL[..count]);
+            }
             Mnemonics::OpCodeError { op } => {
                 /* Unknown Opcode  */
-                return Err(OpcodeError::UnknownMnemonic(op));
+                return self.fault(OpcodeError::UnknownMnemonic(op));
             }
-        };
+        }
         Ok(())
     }
 }
+
+/* Register indexes from x to y, descending if x > y */
+fn register_range(x: u8, y: u8) -> impl Iterator<Item = usize> {
+    let (x, y) = (x as usize, y as usize);
+    (0..=x.abs_diff(y)).map(move |step| if x <= y { x + step } else { x - step })
+}
@@ -0,0 +1,136 @@
+use crate::emulator::CPU;
+use crate::emulator::errors::EmuError;
+use crate::emulator::history::{History, HistoryEntry};
+use crate::emulator::memory::Memory;
+use crate::emulator::mnemonics::disassemble;
+use std::fmt;
+
+/* Instructions disassembled before and after the faulting PC */
+pub const DISASSEMBLY_CONTEXT: usize = 5;
+
+/* Snapshot of the machine state when emulation halted on an error */
+#[allow(non_snake_case)]
+#[derive(Debug, Clone)]
+pub struct CrashReport {
+    pub error: String,
+    pub pc: u16,
+    pub opcode: u16,
+    pub V: [u8; 16],
+    pub I: u16,
+    pub PROGRAM_COUNTER: u16,
+    pub STACK_POINTER: u8,
+    pub D_TIMER: u8,
+    pub S_TIMER: u8,
+    pub stack: Vec<u16>,
+    /* (address, opcode) pairs around the faulting PC */
+    pub disassembly: Vec<(u16, u16)>,
+    pub history: Vec<HistoryEntry>,
+}
+impl CrashReport {
+    pub fn new(error: &EmuError, cpu: &CPU, memory: &Memory, history: &History) -> CrashReport {
+        let (pc, opcode) = match *error {
+            EmuError::Cpu { pc, opcode, .. } => (pc, opcode),
+            _ => (
+                cpu.PROGRAM_COUNTER,
+                read_opcode(memory, cpu.PROGRAM_COUNTER),
+            ),
+        };
+        /* Instructions are not aligned, so walk two bytes at a time from the faulting PC */
+        let start = pc.saturating_sub(2 * DISASSEMBLY_CONTEXT as u16);
+        let disassembly = (0..=2 * DISASSEMBLY_CONTEXT as u16)
+            .map(|i| start.wrapping_add(2 * i))
+            .filter(|&addr| (addr as usize) + 1 < memory.RAM.len())
+            .map(|addr| (addr, read_opcode(memory, addr)))
+            .collect();
+        CrashReport {
+            error: error.to_string(),
+            pc,
+            opcode,
+            V: cpu.V,
+            I: cpu.I,
+            PROGRAM_COUNTER: cpu.PROGRAM_COUNTER,
+            STACK_POINTER: cpu.STACK_POINTER,
+            D_TIMER: cpu.D_TIMER,
+            S_TIMER: cpu.S_TIMER,
+            stack: memory.STACK.clone(),
+            disassembly,
+            history: history.iter().copied().collect(),
+        }
+    }
+    /* Writes the report to a file */
+    pub fn save<P: AsRef<std::path::Path>>(&self, path: P) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+}
+
+/* Reads a big endian opcode, 0 if it lies outside RAM */
+fn read_opcode(memory: &Memory, addr: u16) -> u16 {
+    let addr = addr as usize;
+    match (memory.RAM.get(addr), memory.RAM.get(addr + 1)) {
+        (Some(&hi), Some(&lo)) => u16::from_be_bytes([hi, lo]),
+        _ => 0,
+    }
+}
+
+impl fmt::Display for CrashReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "=== CHIP-8 crash report ===")?;
+        writeln!(f, "Error:  {}", self.error)?;
+        writeln!(
+            f,
+            "Fault:  {:#06x}  {:04X}  {}",
+            self.pc,
+            self.opcode,
+            disassemble(self.opcode)
+        )?;
+
+        writeln!(f, "\nRegisters:")?;
+        for (i, regs) in self.V.chunks(8).enumerate() {
+            let line: Vec<String> = regs
+                .iter()
+                .enumerate()
+                .map(|(j, v)| format!("V{:X}={:02X}", i * 8 + j, v))
+                .collect();
+            writeln!(f, "  {}", line.join(" "))?;
+        }
+        writeln!(
+            f,
+            "  I={:04X} PC={:04X} SP={:02X} DT={:02X} ST={:02X}",
+            self.I, self.PROGRAM_COUNTER, self.STACK_POINTER, self.D_TIMER, self.S_TIMER
+        )?;
+
+        writeln!(f, "\nStack ({} entries, top first):", self.stack.len())?;
+        if self.stack.is_empty() {
+            writeln!(f, "  <empty>")?;
+        }
+        for (depth, addr) in self.stack.iter().rev().enumerate() {
+            writeln!(f, "  #{depth:<2} {addr:#06x}")?;
+        }
+
+        writeln!(f, "\nDisassembly:")?;
+        for &(addr, opcode) in &self.disassembly {
+            let marker = if addr == self.pc { '>' } else { ' ' };
+            writeln!(
+                f,
+                "{marker} {addr:#06x}  {opcode:04X}  {}",
+                disassemble(opcode)
+            )?;
+        }
+
+        writeln!(
+            f,
+            "\nLast {} instructions, oldest first:",
+            self.history.len()
+        )?;
+        for entry in &self.history {
+            writeln!(
+                f,
+                "  {:#06x}  {:04X}  {}",
+                entry.pc,
+                entry.opcode,
+                disassemble(entry.opcode)
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -1,34 +1,60 @@
-use minifb::{Scale, Window, WindowOptions};
+use crate::emulator::hash::fnv1a;
 
 /*CHIP-8 Window Height and Width */
 pub const WINDOW_WIDTH: usize = 64;
 pub const WINDOW_HEIGHT: usize = 32;
+/* SUPER-CHIP and XO-CHIP high resolution Height and Width */
+pub const HIRES_WIDTH: usize = 128;
+pub const HIRES_HEIGHT: usize = 64;
+
+/* XO-CHIP bitplanes, plane 1 is the classic CHIP-8 screen */
+pub const NUM_PLANES: usize = 2;
+pub const PLANE_1: u8 = 0b01;
+pub const ALL_PLANES: u8 = 0b11;
 
 /* 0x000000 = Black or 0 */
 const PIXEL_OFF: u32 = 0x000000;
 /* 0xFFFFFF = White or 1 */
 const PIXEL_ON: u32 = 0xFFFFFF;
 
+/* Colour of each plane combination: none, plane 1, plane 2, both */
+pub type Palette = [u32; 1 << NUM_PLANES];
+pub const DEFAULT_PALETTE: Palette = [PIXEL_OFF, PIXEL_ON, 0xAAAAAA, 0x555555];
+
+/* Display contents without the derived pixel buffer, kept by snapshots */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisplayState {
+    pub planes: Vec<u8>,
+    pub hires: bool,
+    pub selected_planes: u8,
+}
+
+/*
+    The pixel buffer has the platform's native resolution.
+    In low resolution mode a 128x64 buffer draws every CHIP-8 pixel as a 2x2 block,
+    so the window keeps its size when a ROM switches modes.
+
+    `planes` holds the XO-CHIP plane bits of every pixel, `pixels_buffer`
+    the matching palette colours ready to present.
+*/
 #[derive(Debug)]
 pub struct Display {
-    pub pixels_buffer: [u32; WINDOW_WIDTH * WINDOW_HEIGHT],
-    pub window: Window,
+    pub pixels_buffer: Vec<u32>,
+    planes: Vec<u8>,
+    /* Pixel buffer Width and Height */
+    buffer_width: usize,
+    buffer_height: usize,
+    /* High resolution mode, the CHIP-8 screen matches the pixel buffer */
+    hires: bool,
+    /* Planes affected by drawing, clearing and scrolling (FN01) */
+    selected_planes: u8,
+    palette: Palette,
+    /* Set whenever the pixel buffer is modified */
+    changed: bool,
 }
 impl Default for Display {
     fn default() -> Display {
-        Display {
-            pixels_buffer: [0; WINDOW_WIDTH * WINDOW_HEIGHT],
-            window: Window::new(
-                "CHIP-8",
-                WINDOW_WIDTH,
-                WINDOW_HEIGHT,
-                WindowOptions {
-                    scale: Scale::X16,
-                    ..WindowOptions::default()
-                },
-            )
-            .expect("Failed to create window."),
-        }
+        Display::with_resolution(WINDOW_WIDTH, WINDOW_HEIGHT)
     }
 }
 impl Display {
@@ -37,53 +63,213 @@ impl Display {
             ..Default::default()
         }
     }
+    /* Display with a native resolution of width x height, either 64x32 or 128x64 */
+    pub fn with_resolution(width: usize, height: usize) -> Self {
+        Self {
+            pixels_buffer: vec![DEFAULT_PALETTE[0]; width * height],
+            planes: vec![0; width * height],
+            buffer_width: width,
+            buffer_height: height,
+            hires: false,
+            selected_planes: PLANE_1,
+            palette: DEFAULT_PALETTE,
+            changed: false,
+        }
+    }
+    /* Clears all planes and returns to low resolution, keeping the palette */
+    pub fn reset(&mut self) {
+        *self = Display {
+            palette: self.palette,
+            ..Display::with_resolution(self.buffer_width, self.buffer_height)
+        };
+        self.refresh();
+    }
     #[inline]
-    /* Sets display target FPS to 60 */
-    pub fn set_target_fps(&mut self) {
-        self.window.set_target_fps(60);
+    /* Returns the pixel buffer width */
+    pub fn buffer_width(&self) -> usize {
+        self.buffer_width
     }
     #[inline]
-    /* Returns if window is open */
-    pub fn window_is_open(&self) -> bool {
-        self.window.is_open()
+    /* Returns the pixel buffer height */
+    pub fn buffer_height(&self) -> usize {
+        self.buffer_height
+    }
+    #[inline]
+    /* Returns the screen width seen by the CHIP-8 program in the current mode */
+    pub fn width(&self) -> usize {
+        if self.hires {
+            self.buffer_width
+        } else {
+            WINDOW_WIDTH
+        }
+    }
+    #[inline]
+    /* Returns the screen height seen by the CHIP-8 program in the current mode */
+    pub fn height(&self) -> usize {
+        if self.hires {
+            self.buffer_height
+        } else {
+            WINDOW_HEIGHT
+        }
+    }
+    #[inline]
+    /* Returns if the display is in high resolution mode */
+    pub fn is_hires(&self) -> bool {
+        self.hires
+    }
+    #[inline]
+    /* Size of a CHIP-8 pixel in buffer pixels */
+    fn scale(&self) -> usize {
+        self.buffer_width / self.width()
     }
     #[inline]
     /* Returns the current postion of x on the display  */
     pub fn get_x_postion(&self, x: usize) -> usize {
-        x % WINDOW_WIDTH
+        x % self.width()
     }
     #[inline]
     /* Returns the current postion of y on the display  */
     pub fn get_y_postion(&self, y: usize) -> usize {
-        y % WINDOW_HEIGHT
+        y % self.height()
+    }
+    #[inline]
+    pub fn palette(&self) -> Palette {
+        self.palette
+    }
+    /* Sets the colours of each plane combination and recolours the pixel buffer */
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+        self.refresh();
+    }
+    #[inline]
+    pub fn selected_planes(&self) -> u8 {
+        self.selected_planes
+    }
+    #[inline]
+    /* Selects the planes affected by drawing, clearing and scrolling (FN01) */
+    pub fn select_planes(&mut self, planes: u8) {
+        self.selected_planes = planes & ALL_PLANES;
+    }
+    #[inline]
+    /* Returns the plane bits of the pixel at some index */
+    pub fn get_planes(&self, index: usize) -> u8 {
+        self.planes[index]
+    }
+    /* Recomputes the pixel colours from the plane bits */
+    fn refresh(&mut self) {
+        for (pixel, &planes) in self.pixels_buffer.iter_mut().zip(&self.planes) {
+            *pixel = self.palette[planes as usize];
+        }
+        self.changed = true;
+    }
+    /* Captures the plane bits and mode, the pixel buffer is rebuilt from them on restore */
+    pub fn state(&self) -> DisplayState {
+        DisplayState {
+            planes: self.planes.clone(),
+            hires: self.hires,
+            selected_planes: self.selected_planes,
+        }
+    }
+    /* Restores a captured state, a state of another resolution is ignored */
+    pub fn restore(&mut self, state: &DisplayState) {
+        if state.planes.len() != self.planes.len() {
+            return;
+        }
+        self.planes.copy_from_slice(&state.planes);
+        self.hires = state.hires;
+        self.selected_planes = state.selected_planes & ALL_PLANES;
+        self.refresh();
+    }
+    /* Switches between low (00FE) and high (00FF) resolution, clearing the screen */
+    pub fn set_hires(&mut self, hires: bool) {
+        /* A 64x32 buffer has no high resolution mode */
+        self.hires = hires && self.buffer_width > WINDOW_WIDTH;
+        self.planes.fill(0);
+        self.refresh();
+    }
+    /* Moves the selected planes by (dx, dy) buffer pixels, uncovered pixels turn off */
+    fn shift_planes(&mut self, dx: isize, dy: isize) {
+        let (width, height) = (self.buffer_width as isize, self.buffer_height as isize);
+        let mask = self.selected_planes;
+        let source = self.planes.clone();
+        for y in 0..height {
+            for x in 0..width {
+                let (sx, sy) = (x - dx, y - dy);
+                let moved = if (0..width).contains(&sx) && (0..height).contains(&sy) {
+                    source[(sy * width + sx) as usize] & mask
+                } else {
+                    0
+                };
+                let idx = (y * width + x) as usize;
+                self.planes[idx] = (source[idx] & !mask) | moved;
+            }
+        }
+        self.refresh();
+    }
+    /* Scrolls the screen down by n CHIP-8 pixels */
+    pub fn scroll_down(&mut self, n: usize) {
+        self.shift_planes(0, (n * self.scale()) as isize);
+    }
+    /* Scrolls the screen up by n CHIP-8 pixels */
+    pub fn scroll_up(&mut self, n: usize) {
+        self.shift_planes(0, -((n * self.scale()) as isize));
+    }
+    /* Scrolls the screen right by n CHIP-8 pixels */
+    pub fn scroll_right(&mut self, n: usize) {
+        self.shift_planes((n * self.scale()) as isize, 0);
+    }
+    /* Scrolls the screen left by n CHIP-8 pixels */
+    pub fn scroll_left(&mut self, n: usize) {
+        self.shift_planes(-((n * self.scale()) as isize), 0);
+    }
+    /* Flips (XOR) the CHIP-8 pixel at (x, y) on some plane, returns if it was on (collision) */
+    pub fn toggle_pixel(&mut self, x: usize, y: usize, plane: u8) -> bool {
+        let scale = self.scale();
+        let idx = y * scale * self.buffer_width + x * scale;
+        let collision = self.planes[idx] & plane != 0;
+        for dy in 0..scale {
+            for dx in 0..scale {
+                let idx = idx + dy * self.buffer_width + dx;
+                self.planes[idx] ^= plane;
+                self.pixels_buffer[idx] = self.palette[self.planes[idx] as usize];
+            }
+        }
+        self.changed = true;
+        collision
     }
     /* Sets pixel in pixel buffer */
     pub fn set_pixels(&mut self, index: usize) {
-        self.pixels_buffer[index] = if self.pixels_buffer[index] == PIXEL_OFF {
-            PIXEL_ON
-        } else {
-            PIXEL_OFF
-        }
+        self.planes[index] ^= self.selected_planes;
+        self.pixels_buffer[index] = self.palette[self.planes[index] as usize];
+        self.changed = true;
     }
     #[inline]
     /* Returns if pixel in pixel buffer is on */
     pub fn is_pixel_on(&self, index: usize) -> bool {
-        self.pixels_buffer[index] == PIXEL_ON
+        self.planes[index] != 0
     }
     #[inline]
-    /* Clears pixel buffer */
+    /* Clears the selected planes of the pixel buffer */
     pub fn clear(&mut self) {
-        self.pixels_buffer = [PIXEL_OFF; WINDOW_WIDTH * WINDOW_HEIGHT];
+        let mask = self.selected_planes;
+        self.planes.iter_mut().for_each(|planes| *planes &= !mask);
+        self.refresh();
     }
     #[inline]
     /* Returns pixel at some index */
     pub fn get_pixel(&self, index: usize) -> u32 {
         self.pixels_buffer[index]
     }
-    /* Updates display with pixel buffer */
-    pub fn update(&mut self) {
-        self.window
-            .update_with_buffer(&self.pixels_buffer, WINDOW_WIDTH, WINDOW_HEIGHT)
-            .unwrap();
+    #[inline]
+    /* Returns if the pixel buffer was modified since the last call, then resets the flag */
+    pub fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+    /* Hash of the screen contents and mode, independent of the palette */
+    pub fn frame_hash(&self) -> u64 {
+        let mut bytes = Vec::with_capacity(self.planes.len() + 1);
+        bytes.push(self.hires as u8);
+        bytes.extend_from_slice(&self.planes);
+        fnv1a(&bytes)
     }
 }
@@ -5,11 +5,11 @@ pub enum MemoryError {
     #[error("Memory Overflow")]
     MemoryOverflow,
 
-    #[error("Failed to read RAM address: {0}")]
-    MemoryReadError(u16),
+    #[error("Failed to read RAM address: {0:#06x}")]
+    MemoryReadError(usize),
 
-    #[error("Failed writing value ({value}) to RAM address ({addr})")]
-    MemoryWriteError { addr: u16, value: u8 },
+    #[error("Failed writing value ({value}) to RAM address ({addr:#06x})")]
+    MemoryWriteError { addr: usize, value: u8 },
 
     #[error("Stack overflow")]
     StackOverflow,
@@ -33,6 +33,119 @@ pub enum OpcodeError {
 pub enum KeyboardError {
     #[error("Unknown key")]
     UnknownKey,
+
+    #[error("Key {0:#04x} is not on the keypad")]
+    KeyOutOfRange(u8),
+}
+
+/* Errors raised while executing an instruction */
+#[derive(Error, Debug)]
+pub enum CpuError {
+    #[error(transparent)]
+    Memory(#[from] MemoryError),
+
+    #[error(transparent)]
+    Opcode(#[from] OpcodeError),
+
+    #[error(transparent)]
+    Keyboard(#[from] KeyboardError),
+}
+
+/* What the CPU does on a bad memory access, stack error, unknown opcode or key */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ErrorPolicy {
+    /* Stop emulation with an error */
+    #[default]
+    Halt,
+    /* Wrap addresses around the end of RAM and keys to 0-F, ignore stack and opcode errors */
+    Wrap,
+    /* Read 0, drop writes, skip bad stack operations and unknown opcodes, keys are up */
+    Ignore,
+}
+impl ErrorPolicy {
+    pub const ALL: [ErrorPolicy; 3] = [ErrorPolicy::Halt, ErrorPolicy::Wrap, ErrorPolicy::Ignore];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorPolicy::Halt => "halt",
+            ErrorPolicy::Wrap => "wrap",
+            ErrorPolicy::Ignore => "ignore",
+        }
+    }
+}
+impl std::fmt::Display for ErrorPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+impl std::str::FromStr for ErrorPolicy {
+    type Err = String;
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        ErrorPolicy::ALL
+            .into_iter()
+            .find(|policy| policy.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("unknown error policy '{name}', expected halt, wrap or ignore"))
+    }
+}
+
+/* Errors loading a save state */
+#[derive(Error, Debug)]
+pub enum SaveStateError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("Not a CHIP-8 save state")]
+    BadMagic,
+
+    #[error(
+        "Save state format version {found} (written by chip8 {emulator}) is not supported, expected version {expected}"
+    )]
+    Version {
+        found: u16,
+        expected: u16,
+        emulator: String,
+    },
+
+    #[error("Save state is for another ROM (hash {found:016x}, loaded ROM {expected:016x})")]
+    RomMismatch { expected: u64, found: u64 },
+
+    #[error("Save state is for the {found} platform, running {expected}")]
+    PlatformMismatch { expected: String, found: String },
+
+    #[error("Save slots need a ROM loaded from a file")]
+    NoSlots,
+
+    #[error("Corrupt save state: {0}")]
+    Corrupt(&'static str),
+}
+
+/* Errors reading or replaying an input movie */
+#[derive(Error, Debug)]
+pub enum MovieError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("Movie line {line}: {message}")]
+    Syntax { line: usize, message: String },
+
+    #[error("Movie format version {found} is not supported, expected version {expected}")]
+    Version { found: u16, expected: u16 },
+
+    #[error("Movie is missing its '{0}' header")]
+    MissingHeader(&'static str),
+
+    #[error("Movie was recorded with another ROM (hash {found:016x}, loaded ROM {expected:016x})")]
+    RomMismatch { expected: u64, found: u64 },
+}
+
+/* Errors reading a scripted key press file */
+#[derive(Error, Debug)]
+pub enum KeyScriptError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("Key script line {line}: {message}")]
+    Syntax { line: usize, message: String },
 }
 
 #[derive(Error, Debug)]
@@ -48,4 +161,21 @@ pub enum EmuError {
 
     #[error(transparent)]
     Keyboard(#[from] KeyboardError),
+
+    #[error(transparent)]
+    SaveState(#[from] SaveStateError),
+
+    #[error(transparent)]
+    Movie(#[from] MovieError),
+
+    #[error(transparent)]
+    KeyScript(#[from] KeyScriptError),
+
+    #[error("{source} (PC: {pc:#06x}, opcode: {opcode:#06x})")]
+    Cpu {
+        pc: u16,
+        opcode: u16,
+        #[source]
+        source: CpuError,
+    },
 }
@@ -0,0 +1,62 @@
+use std::collections::VecDeque;
+
+/* Number of executed instructions kept for crash reports */
+pub const DEFAULT_HISTORY_LEN: usize = 32;
+
+/* An instruction that was fetched for execution */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub pc: u16,
+    pub opcode: u16,
+}
+
+/* Ring buffer of the last executed instructions, oldest first */
+#[derive(Debug)]
+pub struct History {
+    entries: VecDeque<HistoryEntry>,
+    capacity: usize,
+}
+impl Default for History {
+    fn default() -> History {
+        History::with_capacity(DEFAULT_HISTORY_LEN)
+    }
+}
+impl History {
+    pub fn new() -> History {
+        History {
+            ..Default::default()
+        }
+    }
+    pub fn with_capacity(capacity: usize) -> History {
+        History {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+    /* Records an instruction, dropping the oldest one when full */
+    pub fn push(&mut self, pc: u16, opcode: u16) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(HistoryEntry { pc, opcode });
+    }
+    #[inline]
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    /* Iterates from the oldest to the most recent instruction */
+    pub fn iter(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.entries.iter()
+    }
+}
@@ -2,11 +2,17 @@ use crate::emulator::MemoryError;
 
 /* Public */
 pub const FONT_BASE_ADDR: usize = 0x050;
+/* SUPER-CHIP 8x10 font, right after the small font */
+pub const BIG_FONT_BASE_ADDR: usize = 0x0A0;
 pub const START_ADDR: usize = 0x200;
 
-const RAM_SIZE: usize = 4096;
-const STACK_SIZE: usize = 16;
+pub const RAM_SIZE: usize = 4096;
+/* XO-CHIP address space */
+pub const XO_RAM_SIZE: usize = 65536;
+/* Nested subroutine calls, the same on every platform */
+pub const STACK_SIZE: usize = 16;
 const FONTSET_SIZE: usize = 80;
+const BIG_FONTSET_SIZE: usize = 160;
 
 const FONTSET: [u8; FONTSET_SIZE] = [
     0xF0, 0x90, 0x90, 0x90, 0xF0, /* 0 */
@@ -27,17 +33,51 @@ const FONTSET: [u8; FONTSET_SIZE] = [
     0xF0, 0x80, 0xF0, 0x80, 0x80, /* F */
 ];
 
+const BIG_FONTSET: [u8; BIG_FONTSET_SIZE] = [
+    0x3C, 0x7E, 0xE7, 0xC3, 0xC3, 0xC3, 0xC3, 0xE7, 0x7E, 0x3C, /* 0 */
+    0x18, 0x38, 0x58, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x3C, /* 1 */
+    0x3E, 0x7F, 0xC3, 0x06, 0x0C, 0x18, 0x30, 0x60, 0xFF, 0xFF, /* 2 */
+    0x3C, 0x7E, 0xC3, 0x03, 0x0E, 0x0E, 0x03, 0xC3, 0x7E, 0x3C, /* 3 */
+    0x06, 0x0E, 0x1E, 0x36, 0x66, 0xC6, 0xFF, 0xFF, 0x06, 0x06, /* 4 */
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFC, 0xFE, 0x03, 0xC3, 0x7E, 0x3C, /* 5 */
+    0x3E, 0x7C, 0xC0, 0xC0, 0xFC, 0xFE, 0xC3, 0xC3, 0x7E, 0x3C, /* 6 */
+    0xFF, 0xFF, 0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x60, 0x60, /* 7 */
+    0x3C, 0x7E, 0xC3, 0xC3, 0x7E, 0x7E, 0xC3, 0xC3, 0x7E, 0x3C, /* 8 */
+    0x3C, 0x7E, 0xC3, 0xC3, 0x7F, 0x3F, 0x03, 0x03, 0x3E, 0x7C, /* 9 */
+    0x7E, 0xFF, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, 0xC3, 0xC3, 0xC3, /* A */
+    0xFC, 0xFC, 0xC3, 0xC3, 0xFC, 0xFC, 0xC3, 0xC3, 0xFC, 0xFC, /* B */
+    0x3C, 0xFF, 0xC3, 0xC0, 0xC0, 0xC0, 0xC0, 0xC3, 0xFF, 0x3C, /* C */
+    0xFC, 0xFE, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xFE, 0xFC, /* D */
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, /* E */
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xC0, 0xC0, /* F */
+];
+
+/* Direction of a RAM access */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessKind {
+    Read,
+    Write,
+}
+
+/* A data access to RAM made by an instruction, see CPU::access_log */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryAccess {
+    pub kind: AccessKind,
+    pub addr: usize,
+    pub value: u8,
+}
+
 #[allow(non_snake_case)]
 #[derive(Debug)]
 pub struct Memory {
     pub STACK: Vec<u16>,
-    pub RAM: [u8; RAM_SIZE],
+    pub RAM: Vec<u8>,
 }
 impl Default for Memory {
     fn default() -> Memory {
         Memory {
             STACK: Vec::with_capacity(STACK_SIZE),
-            RAM: [0; RAM_SIZE],
+            RAM: vec![0; RAM_SIZE],
         }
     }
 }
@@ -45,17 +85,24 @@ impl Default for Memory {
 #[allow(non_snake_case)]
 impl Memory {
     pub fn new() -> Memory {
+        Memory::with_size(RAM_SIZE)
+    }
+    /* Memory with `ram_size` bytes of RAM, 4096 for CHIP-8, 65536 for XO-CHIP */
+    pub fn with_size(ram_size: usize) -> Memory {
         let mut memory = Memory {
+            RAM: vec![0; ram_size],
             ..Default::default()
         };
-        /* Load Font in RAM */
+        /* Load Fonts in RAM */
         memory.RAM[FONT_BASE_ADDR..FONT_BASE_ADDR + FONTSET_SIZE].copy_from_slice(&FONTSET);
+        memory.RAM[BIG_FONT_BASE_ADDR..BIG_FONT_BASE_ADDR + BIG_FONTSET_SIZE]
+            .copy_from_slice(&BIG_FONTSET);
         memory
     }
     #[inline]
-    /* Reset memory to default */
+    /* Reset memory to default, keeping the RAM size and font */
     pub fn reset(&mut self) {
-        *self = Memory::default();
+        *self = Memory::with_size(self.RAM.len());
     }
     /* Load ROM into RAM */
     pub fn load<R: AsRef<[u8]>>(&mut self, rom: R) -> Result<(), MemoryError> {
@@ -67,6 +114,23 @@ impl Memory {
         self.RAM[START_ADDR..START_ADDR + rom_len].copy_from_slice(rom);
         Ok(())
     }
+    /* Reads a byte of RAM */
+    pub fn read(&self, addr: usize) -> Result<u8, MemoryError> {
+        self.RAM
+            .get(addr)
+            .copied()
+            .ok_or(MemoryError::MemoryReadError(addr))
+    }
+    /* Writes a byte of RAM */
+    pub fn write(&mut self, addr: usize, value: u8) -> Result<(), MemoryError> {
+        match self.RAM.get_mut(addr) {
+            Some(byte) => {
+                *byte = value;
+                Ok(())
+            }
+            None => Err(MemoryError::MemoryWriteError { addr, value }),
+        }
+    }
     /* Removes and returns top item from stack */
     pub fn stack_pop(&mut self) -> Result<u16, MemoryError> {
         if self.STACK.is_empty() {
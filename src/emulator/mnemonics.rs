@@ -1,12 +1,27 @@
 use crate::emulator::OpcodeError;
+use crate::emulator::platform::InstructionSet;
 
 #[allow(non_camel_case_types)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Mnemonics {
+    /* 00CN - Scroll display N pixels down (SUPER-CHIP) */
+    SCROLL_DOWN { n: u8 },
+    /* 00DN - Scroll display N pixels up (XO-CHIP) */
+    SCROLL_UP { n: u8 },
     /* 00E0 - Clear Screen */
     CLEAR,
     /* 00EE - Return from a subroutine */
     RETURN,
+    /* 00FB - Scroll display 4 pixels right (SUPER-CHIP) */
+    SCROLL_RIGHT,
+    /* 00FC - Scroll display 4 pixels left (SUPER-CHIP) */
+    SCROLL_LEFT,
+    /* 00FD - Exit interpreter (SUPER-CHIP) */
+    EXIT,
+    /* 00FE - Low resolution 64x32 mode (SUPER-CHIP) */
+    LORES,
+    /* 00FF - High resolution 128x64 mode (SUPER-CHIP) */
+    HIRES,
     /* 1NNN - Jump, PC = NNN */
     JUMP { nnn: u16 },
     /* 2NNN - Execute subroutine starting at NNN */
@@ -17,6 +32,10 @@ pub enum Mnemonics {
     SNE_Vx_NN { x: u8, nn: u8 },
     /* 5XY0 - Skip if RAM[Vx] == RAM[Vy] */
     SE_Vx_Vy { x: u8, y: u8 },
+    /* 5XY2 - RAM[I] .. = Vx .. Vy (XO-CHIP) */
+    STORE_RANGE_Vx_Vy { x: u8, y: u8 },
+    /* 5XY3 - Vx .. Vy = RAM[I] .. (XO-CHIP) */
+    LOAD_RANGE_Vx_Vy { x: u8, y: u8 },
     /* 6XNN - RAM[Vx] = NN */
     LOAD_Vx_NN { x: u8, nn: u8 },
     /* 7XNN - RAM[Vx] += NN */
@@ -47,12 +66,19 @@ pub enum Mnemonics {
     JUMP_V0_NNN { nnn: u16 },
     /* CVNN - RAM[Vx] = RandomNum with a mask of NN */
     RAND { x: u8, nn: u8 },
-    /* DXYN - Draw sprite at position Vx, Vy with N bytes, starting at RAM[I], Set VF any pixels changes from 1 -> 0 */
+    /* DXYN - Draw sprite at position Vx, Vy with N bytes, starting at RAM[I], Set VF any pixels changes from 1 -> 0
+    DXY0 - Draw 16x16 sprite (SUPER-CHIP) */
     DRAW { x: u8, y: u8, n: u8 },
     /* EX9E - Skip following (PC += 2) if KEY(Hex) is already = RAM[Vx] */
     SKP_Vx { x: u8 },
     /* EXA1 - Skip following (PC += 2) if KEY(Hex) != Vx */
     SKNP_Vx { x: u8 },
+    /* F000 NNNN - [I] = NNNN, 16 bit address (XO-CHIP) */
+    LOAD_I_LONG,
+    /* FN01 - Select drawing planes N (XO-CHIP) */
+    PLANE { n: u8 },
+    /* F002 - Audio pattern = RAM[I] .. RAM[I + 15] (XO-CHIP) */
+    LOAD_AUDIO,
     /* FX07 - RAM[Vx] = [DELAY_TIMER] */
     LOAD_Vx_DT { x: u8 },
     /* FX0A - Wait for [KEY]press, RAM[Vx] = [KEY] */
@@ -65,12 +91,20 @@ pub enum Mnemonics {
     ADD_I_Vx { x: u8 },
     /* FX29 - [I] =  VxRAM[SPRITE_DATA] ->= RAM[Vx] */
     LOAD_FONT { x: u8 },
+    /* FX30 - [I] = RAM[BIG_SPRITE_DATA] for digit Vx (SUPER-CHIP) */
+    LOAD_BIG_FONT { x: u8 },
+    /* FX3A - [PITCH] = RAM[Vx] (XO-CHIP) */
+    LOAD_PITCH_Vx { x: u8 },
     /* FX33 - [I], [I + 1] [I + 2]  = RAM[Vx], Decimal form */
     LOAD_B_Vx { x: u8 },
     /* FX55 - [I] ..[Ix] = RAM[V0]..RAM[Vx], [I] = I + X + 1 */
     LOAD_I_Vx { x: u8 },
     /* FX65 - [V0] .. [Vx] = [I]..[Ix],  [I] = [I] + X + 1 */
     LOAD_Vx_I { x: u8 },
+    /* FX75 - RPL flags[0] .. flags[x] = V0 .. Vx (SUPER-CHIP) */
+    LOAD_R_Vx { x: u8 },
+    /* FX85 - V0 .. Vx = RPL flags[0] .. flags[x] (SUPER-CHIP) */
+    LOAD_Vx_R { x: u8 },
     /* Opcode Unknown */
     OpCodeError { op: u16 },
 }
@@ -87,13 +121,22 @@ impl TryFrom<u16> for Mnemonics {
         );
 
         let mnemontic = match (op, x, y, n) {
+            (0x0, 0x0, 0xC, _) => Mnemonics::SCROLL_DOWN { n },
+            (0x0, 0x0, 0xD, _) => Mnemonics::SCROLL_UP { n },
             (0x0, 0x0, 0xE, 0x0) => Mnemonics::CLEAR,
             (0x0, 0x0, 0xE, 0xE) => Mnemonics::RETURN,
+            (0x0, 0x0, 0xF, 0xB) => Mnemonics::SCROLL_RIGHT,
+            (0x0, 0x0, 0xF, 0xC) => Mnemonics::SCROLL_LEFT,
+            (0x0, 0x0, 0xF, 0xD) => Mnemonics::EXIT,
+            (0x0, 0x0, 0xF, 0xE) => Mnemonics::LORES,
+            (0x0, 0x0, 0xF, 0xF) => Mnemonics::HIRES,
             (0x1, _, _, _) => Mnemonics::JUMP { nnn },
             (0x2, _, _, _) => Mnemonics::CALL { nnn },
             (0x3, _, _, _) => Mnemonics::SE_Vx_NN { x, nn },
             (0x4, _, _, _) => Mnemonics::SNE_Vx_NN { x, nn },
             (0x5, _, _, 0x0) => Mnemonics::SE_Vx_Vy { x, y },
+            (0x5, _, _, 0x2) => Mnemonics::STORE_RANGE_Vx_Vy { x, y },
+            (0x5, _, _, 0x3) => Mnemonics::LOAD_RANGE_Vx_Vy { x, y },
             (0x6, _, _, _) => Mnemonics::LOAD_Vx_NN { x, nn },
             (0x7, _, _, _) => Mnemonics::ADD_Vx_NN { x, nn },
             (0x8, _, _, 0x0) => Mnemonics::LOAD_Vx_Vy { x, y },
@@ -112,17 +155,231 @@ impl TryFrom<u16> for Mnemonics {
             (0xD, _, _, _) => Mnemonics::DRAW { x, y, n },
             (0xE, _, 0x9, 0xE) => Mnemonics::SKP_Vx { x },
             (0xE, _, 0xA, 0x1) => Mnemonics::SKNP_Vx { x },
+            (0xF, 0x0, 0x0, 0x0) => Mnemonics::LOAD_I_LONG,
+            (0xF, _, 0x0, 0x1) => Mnemonics::PLANE { n: x },
+            (0xF, 0x0, 0x0, 0x2) => Mnemonics::LOAD_AUDIO,
             (0xF, _, 0x0, 0x7) => Mnemonics::LOAD_Vx_DT { x },
             (0xF, _, 0x0, 0xA) => Mnemonics::LOAD_Vx_K { x },
             (0xF, _, 0x1, 0x5) => Mnemonics::LOAD_DT_Vx { x },
             (0xF, _, 0x1, 0x8) => Mnemonics::LOAD_ST_Vx { x },
             (0xF, _, 0x1, 0xE) => Mnemonics::ADD_I_Vx { x },
             (0xF, _, 0x2, 0x9) => Mnemonics::LOAD_FONT { x },
+            (0xF, _, 0x3, 0x0) => Mnemonics::LOAD_BIG_FONT { x },
             (0xF, _, 0x3, 0x3) => Mnemonics::LOAD_B_Vx { x },
+            (0xF, _, 0x3, 0xA) => Mnemonics::LOAD_PITCH_Vx { x },
             (0xF, _, 0x5, 0x5) => Mnemonics::LOAD_I_Vx { x },
             (0xF, _, 0x6, 0x5) => Mnemonics::LOAD_Vx_I { x },
+            (0xF, _, 0x7, 0x5) => Mnemonics::LOAD_R_Vx { x },
+            (0xF, _, 0x8, 0x5) => Mnemonics::LOAD_Vx_R { x },
             (_, _, _, _) => return Err(OpcodeError::UnknownMnemonic(instr)),
         };
         Ok(mnemontic)
     }
 }
+/* Broad instruction categories, used to filter traces */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OpcodeClass {
+    /* Jumps, calls, returns and skips */
+    Flow,
+    /* Register loads and arithmetic */
+    Alu,
+    /* Loads and stores through I */
+    Memory,
+    /* Drawing, scrolling and resolution changes */
+    Display,
+    /* Keypad reads and skips */
+    Input,
+    /* Delay and sound timers, audio pattern and pitch */
+    Timer,
+    /* Exit and unknown opcodes */
+    Machine,
+}
+impl OpcodeClass {
+    pub const ALL: [OpcodeClass; 7] = [
+        OpcodeClass::Flow,
+        OpcodeClass::Alu,
+        OpcodeClass::Memory,
+        OpcodeClass::Display,
+        OpcodeClass::Input,
+        OpcodeClass::Timer,
+        OpcodeClass::Machine,
+    ];
+    pub fn name(&self) -> &'static str {
+        match self {
+            OpcodeClass::Flow => "flow",
+            OpcodeClass::Alu => "alu",
+            OpcodeClass::Memory => "memory",
+            OpcodeClass::Display => "display",
+            OpcodeClass::Input => "input",
+            OpcodeClass::Timer => "timer",
+            OpcodeClass::Machine => "machine",
+        }
+    }
+}
+impl std::fmt::Display for OpcodeClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+impl std::str::FromStr for OpcodeClass {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        OpcodeClass::ALL
+            .into_iter()
+            .find(|class| class.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                let names: Vec<&str> = OpcodeClass::ALL.iter().map(|c| c.name()).collect();
+                format!(
+                    "unknown opcode class '{s}', expected one of: {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+impl Mnemonics {
+    /* Returns the category of the instruction */
+    pub fn class(&self) -> OpcodeClass {
+        match self {
+            Mnemonics::RETURN
+            | Mnemonics::JUMP { .. }
+            | Mnemonics::CALL { .. }
+            | Mnemonics::SE_Vx_NN { .. }
+            | Mnemonics::SNE_Vx_NN { .. }
+            | Mnemonics::SE_Vx_Vy { .. }
+            | Mnemonics::SNE_Vx_Vy { .. }
+            | Mnemonics::JUMP_V0_NNN { .. } => OpcodeClass::Flow,
+            Mnemonics::LOAD_Vx_NN { .. }
+            | Mnemonics::ADD_Vx_NN { .. }
+            | Mnemonics::LOAD_Vx_Vy { .. }
+            | Mnemonics::OR_Vx_Vy { .. }
+            | Mnemonics::AND_Vx_Vy { .. }
+            | Mnemonics::XOR_Vx_Vy { .. }
+            | Mnemonics::ADD_Vx_Vy { .. }
+            | Mnemonics::SUB_Vx_Vy { .. }
+            | Mnemonics::SHR_Vx_Vy { .. }
+            | Mnemonics::SUBN_Vx_Vy { .. }
+            | Mnemonics::SHL_Vx_Vy { .. }
+            | Mnemonics::LOAD_I_NNN { .. }
+            | Mnemonics::LOAD_I_LONG
+            | Mnemonics::RAND { .. }
+            | Mnemonics::ADD_I_Vx { .. }
+            | Mnemonics::LOAD_R_Vx { .. }
+            | Mnemonics::LOAD_Vx_R { .. } => OpcodeClass::Alu,
+            Mnemonics::STORE_RANGE_Vx_Vy { .. }
+            | Mnemonics::LOAD_RANGE_Vx_Vy { .. }
+            | Mnemonics::LOAD_FONT { .. }
+            | Mnemonics::LOAD_BIG_FONT { .. }
+            | Mnemonics::LOAD_B_Vx { .. }
+            | Mnemonics::LOAD_I_Vx { .. }
+            | Mnemonics::LOAD_Vx_I { .. } => OpcodeClass::Memory,
+            Mnemonics::SCROLL_DOWN { .. }
+            | Mnemonics::SCROLL_UP { .. }
+            | Mnemonics::CLEAR
+            | Mnemonics::SCROLL_RIGHT
+            | Mnemonics::SCROLL_LEFT
+            | Mnemonics::LORES
+            | Mnemonics::HIRES
+            | Mnemonics::DRAW { .. }
+            | Mnemonics::PLANE { .. } => OpcodeClass::Display,
+            Mnemonics::SKP_Vx { .. } | Mnemonics::SKNP_Vx { .. } | Mnemonics::LOAD_Vx_K { .. } => {
+                OpcodeClass::Input
+            }
+            Mnemonics::LOAD_Vx_DT { .. }
+            | Mnemonics::LOAD_DT_Vx { .. }
+            | Mnemonics::LOAD_ST_Vx { .. }
+            | Mnemonics::LOAD_AUDIO
+            | Mnemonics::LOAD_PITCH_Vx { .. } => OpcodeClass::Timer,
+            Mnemonics::EXIT | Mnemonics::OpCodeError { .. } => OpcodeClass::Machine,
+        }
+    }
+    /* Returns the smallest instruction set containing the opcode */
+    pub fn instruction_set(&self) -> InstructionSet {
+        match self {
+            Mnemonics::EXIT
+            | Mnemonics::LORES
+            | Mnemonics::HIRES
+            | Mnemonics::LOAD_R_Vx { .. }
+            | Mnemonics::LOAD_Vx_R { .. } => InstructionSet::SuperChip10,
+            Mnemonics::SCROLL_DOWN { .. }
+            | Mnemonics::SCROLL_RIGHT
+            | Mnemonics::SCROLL_LEFT
+            | Mnemonics::LOAD_BIG_FONT { .. } => InstructionSet::SuperChip,
+            Mnemonics::SCROLL_UP { .. }
+            | Mnemonics::STORE_RANGE_Vx_Vy { .. }
+            | Mnemonics::LOAD_RANGE_Vx_Vy { .. }
+            | Mnemonics::LOAD_I_LONG
+            | Mnemonics::PLANE { .. }
+            | Mnemonics::LOAD_AUDIO
+            | Mnemonics::LOAD_PITCH_Vx { .. } => InstructionSet::XoChip,
+            _ => InstructionSet::Chip8,
+        }
+    }
+}
+/* Disassembly, in the common Cowgod/SUPER-CHIP assembler syntax */
+impl std::fmt::Display for Mnemonics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Mnemonics::SCROLL_DOWN { n } => write!(f, "SCD {n}"),
+            Mnemonics::SCROLL_UP { n } => write!(f, "SCU {n}"),
+            Mnemonics::CLEAR => write!(f, "CLS"),
+            Mnemonics::RETURN => write!(f, "RET"),
+            Mnemonics::SCROLL_RIGHT => write!(f, "SCR"),
+            Mnemonics::SCROLL_LEFT => write!(f, "SCL"),
+            Mnemonics::EXIT => write!(f, "EXIT"),
+            Mnemonics::LORES => write!(f, "LOW"),
+            Mnemonics::HIRES => write!(f, "HIGH"),
+            Mnemonics::JUMP { nnn } => write!(f, "JP {nnn:#05X}"),
+            Mnemonics::CALL { nnn } => write!(f, "CALL {nnn:#05X}"),
+            Mnemonics::SE_Vx_NN { x, nn } => write!(f, "SE V{x:X}, {nn:#04X}"),
+            Mnemonics::SNE_Vx_NN { x, nn } => write!(f, "SNE V{x:X}, {nn:#04X}"),
+            Mnemonics::SE_Vx_Vy { x, y } => write!(f, "SE V{x:X}, V{y:X}"),
+            Mnemonics::STORE_RANGE_Vx_Vy { x, y } => write!(f, "SAVE V{x:X} - V{y:X}"),
+            Mnemonics::LOAD_RANGE_Vx_Vy { x, y } => write!(f, "LOAD V{x:X} - V{y:X}"),
+            Mnemonics::LOAD_Vx_NN { x, nn } => write!(f, "LD V{x:X}, {nn:#04X}"),
+            Mnemonics::ADD_Vx_NN { x, nn } => write!(f, "ADD V{x:X}, {nn:#04X}"),
+            Mnemonics::LOAD_Vx_Vy { x, y } => write!(f, "LD V{x:X}, V{y:X}"),
+            Mnemonics::OR_Vx_Vy { x, y } => write!(f, "OR V{x:X}, V{y:X}"),
+            Mnemonics::AND_Vx_Vy { x, y } => write!(f, "AND V{x:X}, V{y:X}"),
+            Mnemonics::XOR_Vx_Vy { x, y } => write!(f, "XOR V{x:X}, V{y:X}"),
+            Mnemonics::ADD_Vx_Vy { x, y } => write!(f, "ADD V{x:X}, V{y:X}"),
+            Mnemonics::SUB_Vx_Vy { x, y } => write!(f, "SUB V{x:X}, V{y:X}"),
+            Mnemonics::SHR_Vx_Vy { x, y } => write!(f, "SHR V{x:X}, V{y:X}"),
+            Mnemonics::SUBN_Vx_Vy { x, y } => write!(f, "SUBN V{x:X}, V{y:X}"),
+            Mnemonics::SHL_Vx_Vy { x, y } => write!(f, "SHL V{x:X}, V{y:X}"),
+            Mnemonics::SNE_Vx_Vy { x, y } => write!(f, "SNE V{x:X}, V{y:X}"),
+            Mnemonics::LOAD_I_NNN { nnn } => write!(f, "LD I, {nnn:#05X}"),
+            Mnemonics::JUMP_V0_NNN { nnn } => write!(f, "JP V0, {nnn:#05X}"),
+            Mnemonics::RAND { x, nn } => write!(f, "RND V{x:X}, {nn:#04X}"),
+            Mnemonics::DRAW { x, y, n } => write!(f, "DRW V{x:X}, V{y:X}, {n}"),
+            Mnemonics::SKP_Vx { x } => write!(f, "SKP V{x:X}"),
+            Mnemonics::SKNP_Vx { x } => write!(f, "SKNP V{x:X}"),
+            Mnemonics::LOAD_I_LONG => write!(f, "LD I, LONG"),
+            Mnemonics::PLANE { n } => write!(f, "PLANE {n}"),
+            Mnemonics::LOAD_AUDIO => write!(f, "AUDIO"),
+            Mnemonics::LOAD_Vx_DT { x } => write!(f, "LD V{x:X}, DT"),
+            Mnemonics::LOAD_Vx_K { x } => write!(f, "LD V{x:X}, K"),
+            Mnemonics::LOAD_DT_Vx { x } => write!(f, "LD DT, V{x:X}"),
+            Mnemonics::LOAD_ST_Vx { x } => write!(f, "LD ST, V{x:X}"),
+            Mnemonics::ADD_I_Vx { x } => write!(f, "ADD I, V{x:X}"),
+            Mnemonics::LOAD_FONT { x } => write!(f, "LD F, V{x:X}"),
+            Mnemonics::LOAD_BIG_FONT { x } => write!(f, "LD HF, V{x:X}"),
+            Mnemonics::LOAD_B_Vx { x } => write!(f, "LD B, V{x:X}"),
+            Mnemonics::LOAD_PITCH_Vx { x } => write!(f, "PITCH V{x:X}"),
+            Mnemonics::LOAD_I_Vx { x } => write!(f, "LD [I], V{x:X}"),
+            Mnemonics::LOAD_Vx_I { x } => write!(f, "LD V{x:X}, [I]"),
+            Mnemonics::LOAD_R_Vx { x } => write!(f, "LD R, V{x:X}"),
+            Mnemonics::LOAD_Vx_R { x } => write!(f, "LD V{x:X}, R"),
+            Mnemonics::OpCodeError { op } => write!(f, "DW {op:#06X}"),
+        }
+    }
+}
+
+/* Decodes a raw opcode, unknown opcodes become OpCodeError */
+pub fn decode(opcode: u16) -> Mnemonics {
+    Mnemonics::try_from(opcode).unwrap_or(Mnemonics::OpCodeError { op: opcode })
+}
+
+/* Disassembles a raw opcode, unknown opcodes are shown as data words */
+pub fn disassemble(opcode: u16) -> String {
+    decode(opcode).to_string()
+}
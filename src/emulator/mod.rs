@@ -1,15 +1,35 @@
-pub mod audio;
 pub mod cpu;
+pub mod crash_report;
 pub mod display;
 pub mod errors;
+pub mod hash;
+pub mod history;
 pub mod keyboard;
 pub mod memory;
 pub mod mnemonics;
+pub mod platform;
+pub mod quirks;
+pub mod rng;
+pub mod savestate;
+pub mod snapshot;
+pub mod step;
+pub mod trace;
 
-pub use audio::Audio;
 pub use cpu::CPU;
-pub use display::{Display, WINDOW_HEIGHT, WINDOW_WIDTH};
-pub use errors::{KeyboardError, MemoryError, OpcodeError};
+pub use crash_report::CrashReport;
+pub use display::{Display, DisplayState, HIRES_HEIGHT, HIRES_WIDTH, WINDOW_HEIGHT, WINDOW_WIDTH};
+pub use errors::{
+    CpuError, ErrorPolicy, KeyScriptError, KeyboardError, MemoryError, MovieError, OpcodeError,
+    SaveStateError,
+};
+pub use history::{History, HistoryEntry};
 pub use keyboard::Keypad;
-pub use memory::Memory;
-pub use mnemonics::Mnemonics;
+pub use memory::{AccessKind, Memory, MemoryAccess};
+pub use mnemonics::{Mnemonics, OpcodeClass, decode, disassemble};
+pub use platform::{InstructionSet, Platform};
+pub use quirks::Quirks;
+pub use rng::{Rng, RngAlgorithm};
+pub use savestate::{SaveState, SaveStateHeader};
+pub use snapshot::{RewindBuffer, Snapshot};
+pub use step::{RunResult, StepResult};
+pub use trace::{Register, Registers, TraceEntry, TraceFilter, TraceSink, Tracer};
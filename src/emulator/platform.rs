@@ -0,0 +1,143 @@
+use crate::emulator::display::{HIRES_HEIGHT, HIRES_WIDTH, WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::emulator::memory::{RAM_SIZE, XO_RAM_SIZE};
+use crate::emulator::quirks::Quirks;
+
+/* Opcode sets, each one a superset of the previous */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum InstructionSet {
+    #[default]
+    Chip8,
+    /* SUPER-CHIP 1.0: hires, 16x16 sprites (8x16 in lores), RPL flags, exit */
+    SuperChip10,
+    /* SUPER-CHIP 1.1: adds scrolling, the big font and 16x16 sprites in lores */
+    SuperChip,
+    /* XO-CHIP: SUPER-CHIP plus bitplanes, 64K memory and audio patterns */
+    XoChip,
+}
+
+/*
+    Named presets bundling quirks, default speed, memory size and display
+    resolution of well-known CHIP-8 implementations.
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Platform {
+    /* The interpreter's original behaviour, all quirks off */
+    #[default]
+    Chip8,
+    /* Original COSMAC VIP CHIP-8 interpreter (1977) */
+    CosmacVip,
+    /* CHIP-48 for the HP48 calculators (1990) */
+    Chip48,
+    /* SUPER-CHIP 1.0 for the HP48 (1991) */
+    SuperChip10,
+    /* SUPER-CHIP 1.1 for the HP48 (1991) */
+    SuperChip11,
+    /* XO-CHIP, as implemented by Octo (2014) */
+    XoChip,
+}
+impl Platform {
+    pub const ALL: [Platform; 6] = [
+        Platform::Chip8,
+        Platform::CosmacVip,
+        Platform::Chip48,
+        Platform::SuperChip10,
+        Platform::SuperChip11,
+        Platform::XoChip,
+    ];
+    /* Short name, as accepted on the command line */
+    pub fn name(&self) -> &'static str {
+        match self {
+            Platform::Chip8 => "chip8",
+            Platform::CosmacVip => "vip",
+            Platform::Chip48 => "chip48",
+            Platform::SuperChip10 => "schip10",
+            Platform::SuperChip11 => "schip11",
+            Platform::XoChip => "xochip",
+        }
+    }
+    pub fn quirks(&self) -> Quirks {
+        match self {
+            Platform::Chip8 => Quirks::default(),
+            Platform::CosmacVip => Quirks {
+                shift: false,
+                load_store_increment: true,
+                jump_with_vx: false,
+                vf_reset: true,
+                clipping: true,
+                display_wait: true,
+            },
+            Platform::Chip48 | Platform::SuperChip10 | Platform::SuperChip11 => Quirks {
+                shift: true,
+                load_store_increment: false,
+                jump_with_vx: true,
+                vf_reset: false,
+                clipping: true,
+                display_wait: false,
+            },
+            Platform::XoChip => Quirks {
+                shift: false,
+                load_store_increment: true,
+                jump_with_vx: false,
+                vf_reset: false,
+                clipping: false,
+                display_wait: false,
+            },
+        }
+    }
+    /* Default instructions executed per 60Hz frame */
+    pub fn cycles_per_frame(&self) -> usize {
+        match self {
+            Platform::Chip8 => 12,
+            Platform::CosmacVip => 15,
+            Platform::Chip48 | Platform::SuperChip10 | Platform::SuperChip11 => 30,
+            Platform::XoChip => 1000,
+        }
+    }
+    /* Opcodes understood by the platform */
+    pub fn instruction_set(&self) -> InstructionSet {
+        match self {
+            Platform::Chip8 | Platform::CosmacVip | Platform::Chip48 => InstructionSet::Chip8,
+            Platform::SuperChip10 => InstructionSet::SuperChip10,
+            Platform::SuperChip11 => InstructionSet::SuperChip,
+            Platform::XoChip => InstructionSet::XoChip,
+        }
+    }
+    /* RAM size in bytes */
+    pub fn ram_size(&self) -> usize {
+        match self {
+            Platform::XoChip => XO_RAM_SIZE,
+            _ => RAM_SIZE,
+        }
+    }
+    /* Native display resolution (Width, Height) */
+    pub fn resolution(&self) -> (usize, usize) {
+        match self {
+            Platform::Chip8 | Platform::CosmacVip | Platform::Chip48 => {
+                (WINDOW_WIDTH, WINDOW_HEIGHT)
+            }
+            Platform::SuperChip10 | Platform::SuperChip11 | Platform::XoChip => {
+                (HIRES_WIDTH, HIRES_HEIGHT)
+            }
+        }
+    }
+}
+impl std::fmt::Display for Platform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+impl std::str::FromStr for Platform {
+    type Err = String;
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Platform::ALL
+            .into_iter()
+            .find(|platform| platform.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                let names: Vec<&str> = Platform::ALL.iter().map(Platform::name).collect();
+                format!(
+                    "unknown platform '{name}', expected one of: {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
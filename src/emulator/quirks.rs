@@ -0,0 +1,26 @@
+/*
+    Behaviours where CHIP-8 implementations disagree.
+    All quirks are off by default, matching the interpreter's original behaviour.
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Quirks {
+    /* 8XY6/8XYE - Shift V[x] in place and ignore V[y], instead of V[x] = V[y] shifted */
+    pub shift: bool,
+    /* FX55/FX65 - Increment [I] by X + 1 after storing/loading registers */
+    pub load_store_increment: bool,
+    /* BNNN - Jump to XNN + V[x] instead of NNN + V[0x0] */
+    pub jump_with_vx: bool,
+    /* 8XY1/8XY2/8XY3 - Reset V[0xF] to 0 after OR, AND and XOR */
+    pub vf_reset: bool,
+    /* DXYN - Clip sprites at the screen edges instead of wrapping them around */
+    pub clipping: bool,
+    /* DXYN - Wait for the next frame (vertical blank) after drawing a sprite */
+    pub display_wait: bool,
+}
+impl Quirks {
+    pub fn new() -> Quirks {
+        Quirks {
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use crate::emulator::memory::{FONT_BASE_ADDR, Memory};
+
+/* 256 bytes walked by the VIP-like algorithm, the fonts stand in for the VIP's interpreter code */
+const VIP_TABLE_ADDR: usize = FONT_BASE_ADDR;
+/* Mixed into seeds, xorshift is stuck at a state of 0 */
+const SEED_MIX: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/* Algorithms CXNN can draw random bytes from */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum RngAlgorithm {
+    /* xorshift64*, fast with a long period */
+    #[default]
+    Xorshift,
+    /*
+        In the style of the COSMAC VIP interpreter, not its actual routine: an
+        8-bit pointer walks 256 bytes of interpreter memory and the byte found
+        there is added to a running value, so the sequence depends on memory
+        and repeats quickly like the VIP's does, but not with the same bytes.
+    */
+    VipLike,
+}
+impl RngAlgorithm {
+    pub const ALL: [RngAlgorithm; 2] = [RngAlgorithm::Xorshift, RngAlgorithm::VipLike];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RngAlgorithm::Xorshift => "xorshift",
+            RngAlgorithm::VipLike => "vip-like",
+        }
+    }
+}
+impl std::fmt::Display for RngAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+impl std::str::FromStr for RngAlgorithm {
+    type Err = String;
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        RngAlgorithm::ALL
+            .into_iter()
+            .find(|algorithm| algorithm.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                format!("unknown random number generator '{name}', expected xorshift or vip-like")
+            })
+    }
+}
+
+/* Random number generator used by CXNN, owned by the CPU so snapshots capture its state */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rng {
+    algorithm: RngAlgorithm,
+    /* xorshift64* state, never 0, or the VIP-like pointer (low byte) and running value (next byte) */
+    state: u64,
+}
+impl Default for Rng {
+    fn default() -> Rng {
+        Rng::from_seed(rand::random())
+    }
+}
+impl Rng {
+    /* Generator seeded from the operating system */
+    pub fn new() -> Rng {
+        Rng {
+            ..Default::default()
+        }
+    }
+    /* xorshift generator, the same seed always gives the same sequence */
+    pub fn from_seed(seed: u64) -> Rng {
+        Rng::with_algorithm(RngAlgorithm::Xorshift, seed)
+    }
+    pub fn with_algorithm(algorithm: RngAlgorithm, seed: u64) -> Rng {
+        let state = match algorithm {
+            RngAlgorithm::Xorshift => match seed ^ SEED_MIX {
+                0 => SEED_MIX,
+                state => state,
+            },
+            RngAlgorithm::VipLike => seed & 0xFFFF,
+        };
+        Rng { algorithm, state }
+    }
+    #[inline]
+    pub fn algorithm(&self) -> RngAlgorithm {
+        self.algorithm
+    }
+    /* Internal state, saved by save states */
+    #[inline]
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+    /* Generator continuing from a saved state, None if the state is invalid for the algorithm */
+    pub fn from_state(algorithm: RngAlgorithm, state: u64) -> Option<Rng> {
+        let valid = match algorithm {
+            RngAlgorithm::Xorshift => state != 0,
+            RngAlgorithm::VipLike => state <= 0xFFFF,
+        };
+        valid.then_some(Rng { algorithm, state })
+    }
+    /* Next random byte, the VIP-like algorithm reads interpreter memory below the program */
+    pub fn next_byte(&mut self, memory: &Memory) -> u8 {
+        match self.algorithm {
+            RngAlgorithm::Xorshift => {
+                self.state ^= self.state >> 12;
+                self.state ^= self.state << 25;
+                self.state ^= self.state >> 27;
+                (self.state.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 56) as u8
+            }
+            RngAlgorithm::VipLike => {
+                let pointer = (self.state as u8).wrapping_add(1);
+                let byte = memory.read(VIP_TABLE_ADDR + pointer as usize).unwrap_or(0);
+                let value = ((self.state >> 8) as u8).rotate_right(1).wrapping_add(byte);
+                self.state = (value as u64) << 8 | pointer as u64;
+                value
+            }
+        }
+    }
+}
@@ -0,0 +1,320 @@
+use crate::emulator::CPU;
+use crate::emulator::cpu::AUDIO_PATTERN_BYTES;
+use crate::emulator::display::{ALL_PLANES, DisplayState};
+use crate::emulator::errors::SaveStateError;
+use crate::emulator::hash::fnv1a;
+use crate::emulator::keyboard::{Keypad, NUM_KEYS};
+use crate::emulator::memory::STACK_SIZE;
+use crate::emulator::platform::InstructionSet;
+use crate::emulator::quirks::Quirks;
+use crate::emulator::rng::{Rng, RngAlgorithm};
+use crate::emulator::snapshot::Snapshot;
+use std::io::{self, Read, Write};
+
+/*
+    Save state file layout, all integers little endian:
+
+        magic               "CH8S"
+        format version      u16
+        emulator version    u8 length + UTF-8
+        platform profile    u8 length + UTF-8 (Platform::name)
+        ROM hash            u64, FNV-1a of the ROM image
+        machine state       the fields of Snapshot, see write_snapshot
+
+    Bump SAVE_STATE_VERSION whenever the machine state layout changes.
+*/
+pub const SAVE_STATE_MAGIC: [u8; 4] = *b"CH8S";
+pub const SAVE_STATE_VERSION: u16 = 3;
+/* Numbered save slots offered by the hotkeys */
+pub const NUM_SAVE_SLOTS: u8 = 9;
+
+/* Hash identifying a ROM image */
+pub fn rom_hash(rom: &[u8]) -> u64 {
+    fnv1a(rom)
+}
+
+/* Describes where a save state came from, checked before it is loaded */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaveStateHeader {
+    pub version: u16,
+    /* Version of the emulator that wrote the state */
+    pub emulator: String,
+    pub platform: String,
+    pub rom_hash: u64,
+}
+
+/* Machine state with its header, as stored on disk */
+#[derive(Debug, Clone)]
+pub struct SaveState {
+    pub header: SaveStateHeader,
+    pub snapshot: Snapshot,
+}
+impl SaveState {
+    pub fn write<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut out = Encoder(writer);
+        out.bytes(&SAVE_STATE_MAGIC)?;
+        out.u16(self.header.version)?;
+        out.string(&self.header.emulator)?;
+        out.string(&self.header.platform)?;
+        out.u64(self.header.rom_hash)?;
+        write_snapshot(&mut out, &self.snapshot)?;
+        out.0.flush()
+    }
+    /* Reads a state, failing early on files that aren't save states or use another format version */
+    pub fn read<R: Read>(reader: R) -> Result<SaveState, SaveStateError> {
+        let mut input = Decoder(reader);
+        let mut magic = [0; 4];
+        input.bytes(&mut magic)?;
+        if magic != SAVE_STATE_MAGIC {
+            return Err(SaveStateError::BadMagic);
+        }
+        let version = input.u16()?;
+        let emulator = input.string()?;
+        if version != SAVE_STATE_VERSION {
+            return Err(SaveStateError::Version {
+                found: version,
+                expected: SAVE_STATE_VERSION,
+                emulator,
+            });
+        }
+        let header = SaveStateHeader {
+            version,
+            emulator,
+            platform: input.string()?,
+            rom_hash: input.u64()?,
+        };
+        let snapshot = read_snapshot(&mut input)?;
+        Ok(SaveState { header, snapshot })
+    }
+    pub fn save<P: AsRef<std::path::Path>>(&self, path: P) -> io::Result<()> {
+        let file = std::fs::File::create(path)?;
+        self.write(io::BufWriter::new(file))
+    }
+    pub fn load<P: AsRef<std::path::Path>>(path: P) -> Result<SaveState, SaveStateError> {
+        let file = std::fs::File::open(path)?;
+        SaveState::read(io::BufReader::new(file))
+    }
+}
+
+fn write_snapshot<W: Write>(out: &mut Encoder<W>, snapshot: &Snapshot) -> io::Result<()> {
+    let cpu = &snapshot.cpu;
+    out.bytes(&cpu.V)?;
+    out.u16(cpu.I)?;
+    out.u8(cpu.S_TIMER)?;
+    out.u8(cpu.D_TIMER)?;
+    out.u16(cpu.PROGRAM_COUNTER)?;
+    out.u8(cpu.STACK_POINTER)?;
+    out.bytes(&cpu.RPL)?;
+    match &cpu.AUDIO_PATTERN {
+        Some(pattern) => {
+            out.u8(1)?;
+            out.bytes(pattern)?;
+        }
+        None => out.u8(0)?,
+    }
+    out.u8(cpu.PITCH)?;
+    out.u8(quirk_bits(&cpu.quirks))?;
+    out.u8(match cpu.instruction_set {
+        InstructionSet::Chip8 => 0,
+        InstructionSet::SuperChip => 1,
+        InstructionSet::XoChip => 2,
+        InstructionSet::SuperChip10 => 3,
+    })?;
+    out.u8(cpu.exited as u8)?;
+    out.u8(cpu.vblank_wait as u8)?;
+    out.u8(cpu.rng.algorithm() as u8)?;
+    out.u64(cpu.rng.state())?;
+
+    out.u8(snapshot.stack.len() as u8)?;
+    for &addr in &snapshot.stack {
+        out.u16(addr)?;
+    }
+    out.u32(snapshot.ram.len() as u32)?;
+    out.bytes(&snapshot.ram)?;
+
+    out.u32(snapshot.display.planes.len() as u32)?;
+    out.bytes(&snapshot.display.planes)?;
+    out.u8(snapshot.display.hires as u8)?;
+    out.u8(snapshot.display.selected_planes)?;
+
+    let keys = (0..NUM_KEYS)
+        .filter(|&key| snapshot.keypad.is_key_down(key))
+        .fold(0u16, |keys, key| keys | 1 << key);
+    out.u16(keys)?;
+    out.u32(snapshot.timer_accumulator)?;
+    out.u64(snapshot.cycles)
+}
+
+fn read_snapshot<R: Read>(input: &mut Decoder<R>) -> Result<Snapshot, SaveStateError> {
+    let mut cpu = CPU::new();
+    input.bytes(&mut cpu.V)?;
+    cpu.I = input.u16()?;
+    cpu.S_TIMER = input.u8()?;
+    cpu.D_TIMER = input.u8()?;
+    cpu.PROGRAM_COUNTER = input.u16()?;
+    cpu.STACK_POINTER = input.u8()?;
+    input.bytes(&mut cpu.RPL)?;
+    cpu.AUDIO_PATTERN = match input.u8()? {
+        0 => None,
+        _ => {
+            let mut pattern = [0; AUDIO_PATTERN_BYTES];
+            input.bytes(&mut pattern)?;
+            Some(pattern)
+        }
+    };
+    cpu.PITCH = input.u8()?;
+    cpu.quirks = quirks_from_bits(input.u8()?);
+    cpu.instruction_set = match input.u8()? {
+        0 => InstructionSet::Chip8,
+        1 => InstructionSet::SuperChip,
+        2 => InstructionSet::XoChip,
+        3 => InstructionSet::SuperChip10,
+        _ => return Err(SaveStateError::Corrupt("unknown instruction set")),
+    };
+    cpu.exited = input.u8()? != 0;
+    cpu.vblank_wait = input.u8()? != 0;
+    let algorithm = match input.u8()? {
+        0 => RngAlgorithm::Xorshift,
+        1 => RngAlgorithm::VipLike,
+        _ => return Err(SaveStateError::Corrupt("unknown random number generator")),
+    };
+    cpu.rng = Rng::from_state(algorithm, input.u64()?).ok_or(SaveStateError::Corrupt(
+        "invalid random number generator state",
+    ))?;
+
+    let stack_len = input.u8()?;
+    if stack_len as usize > STACK_SIZE {
+        return Err(SaveStateError::Corrupt(
+            "stack deeper than the platform allows",
+        ));
+    }
+    let stack = (0..stack_len)
+        .map(|_| input.u16())
+        .collect::<Result<_, _>>()?;
+    let ram = input.vec()?;
+    let planes = input.vec()?;
+    if planes.iter().any(|&pixel| pixel & !ALL_PLANES != 0) {
+        return Err(SaveStateError::Corrupt("pixel outside the display planes"));
+    }
+    let display = DisplayState {
+        planes,
+        hires: input.u8()? != 0,
+        selected_planes: input.u8()?,
+    };
+
+    let keys = input.u16()?;
+    let mut keypad = Keypad::new();
+    for key in 0..NUM_KEYS {
+        keypad.set_key(key, keys & 1 << key != 0);
+    }
+    let timer_accumulator = input.u32()?;
+    if timer_accumulator >= crate::FRAME_RATE {
+        return Err(SaveStateError::Corrupt("timer accumulator out of range"));
+    }
+    Ok(Snapshot {
+        cpu,
+        stack,
+        ram,
+        display,
+        keypad,
+        timer_accumulator,
+        cycles: input.u64()?,
+    })
+}
+
+fn quirk_bits(quirks: &Quirks) -> u8 {
+    [
+        quirks.shift,
+        quirks.load_store_increment,
+        quirks.jump_with_vx,
+        quirks.vf_reset,
+        quirks.clipping,
+        quirks.display_wait,
+    ]
+    .iter()
+    .enumerate()
+    .fold(0, |bits, (bit, &on)| bits | (on as u8) << bit)
+}
+fn quirks_from_bits(bits: u8) -> Quirks {
+    let bit = |n: u8| bits & 1 << n != 0;
+    Quirks {
+        shift: bit(0),
+        load_store_increment: bit(1),
+        jump_with_vx: bit(2),
+        vf_reset: bit(3),
+        clipping: bit(4),
+        display_wait: bit(5),
+    }
+}
+
+/* Little endian writer */
+struct Encoder<W: Write>(W);
+impl<W: Write> Encoder<W> {
+    fn bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.0.write_all(bytes)
+    }
+    fn u8(&mut self, value: u8) -> io::Result<()> {
+        self.bytes(&[value])
+    }
+    fn u16(&mut self, value: u16) -> io::Result<()> {
+        self.bytes(&value.to_le_bytes())
+    }
+    fn u32(&mut self, value: u32) -> io::Result<()> {
+        self.bytes(&value.to_le_bytes())
+    }
+    fn u64(&mut self, value: u64) -> io::Result<()> {
+        self.bytes(&value.to_le_bytes())
+    }
+    fn string(&mut self, value: &str) -> io::Result<()> {
+        let bytes = &value.as_bytes()[..value.len().min(u8::MAX as usize)];
+        self.u8(bytes.len() as u8)?;
+        self.bytes(bytes)
+    }
+}
+
+/* Little endian reader, a truncated file is reported as corrupt */
+struct Decoder<R: Read>(R);
+impl<R: Read> Decoder<R> {
+    fn bytes(&mut self, buffer: &mut [u8]) -> Result<(), SaveStateError> {
+        self.0
+            .read_exact(buffer)
+            .map_err(|error| match error.kind() {
+                io::ErrorKind::UnexpectedEof => SaveStateError::Corrupt("file is truncated"),
+                _ => SaveStateError::Io(error),
+            })
+    }
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], SaveStateError> {
+        let mut buffer = [0; N];
+        self.bytes(&mut buffer)?;
+        Ok(buffer)
+    }
+    fn u8(&mut self) -> Result<u8, SaveStateError> {
+        Ok(self.array::<1>()?[0])
+    }
+    fn u16(&mut self) -> Result<u16, SaveStateError> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+    fn u32(&mut self) -> Result<u32, SaveStateError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+    fn u64(&mut self) -> Result<u64, SaveStateError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+    /* u32 length prefixed bytes, capped so a corrupt length can't exhaust memory */
+    fn vec(&mut self) -> Result<Vec<u8>, SaveStateError> {
+        const MAX_LEN: usize = 1 << 20;
+        let len = self.u32()? as usize;
+        if len > MAX_LEN {
+            return Err(SaveStateError::Corrupt("length out of range"));
+        }
+        let mut buffer = vec![0; len];
+        self.bytes(&mut buffer)?;
+        Ok(buffer)
+    }
+    fn string(&mut self) -> Result<String, SaveStateError> {
+        let len = self.u8()? as usize;
+        let mut buffer = vec![0; len];
+        self.bytes(&mut buffer)?;
+        String::from_utf8(buffer).map_err(|_| SaveStateError::Corrupt("invalid text"))
+    }
+}
@@ -0,0 +1,104 @@
+use crate::emulator::CPU;
+use crate::emulator::display::DisplayState;
+use crate::emulator::keyboard::Keypad;
+use std::collections::VecDeque;
+
+/* Complete machine state at an instruction boundary, see CHIP8::snapshot and CHIP8::restore */
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    /* Registers, timers, quirks and the CXNN random number generator */
+    pub cpu: CPU,
+    pub stack: Vec<u16>,
+    pub ram: Vec<u8>,
+    pub display: DisplayState,
+    pub keypad: Keypad,
+    pub timer_accumulator: u32,
+    /* Instructions executed when the snapshot was taken */
+    pub cycles: u64,
+}
+
+/* Snapshot stored without its RAM, which is rebuilt from the newer snapshots */
+#[derive(Debug)]
+struct RewindEntry {
+    snapshot: Snapshot,
+    /* (address, value) pairs turning this entry's RAM into the previous entry's */
+    ram_undo: Vec<(u16, u8)>,
+}
+
+/* Ring buffer of snapshots, newest last, RAM is kept once and stored as deltas */
+#[derive(Debug)]
+pub struct RewindBuffer {
+    entries: VecDeque<RewindEntry>,
+    /* RAM of the newest entry */
+    ram: Vec<u8>,
+    capacity: usize,
+}
+impl RewindBuffer {
+    pub fn new(capacity: usize) -> RewindBuffer {
+        RewindBuffer {
+            entries: VecDeque::new(),
+            ram: Vec::new(),
+            capacity,
+        }
+    }
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.ram.clear();
+    }
+    /* Instruction count of the newest snapshot */
+    pub fn newest_cycles(&self) -> Option<u64> {
+        self.entries.back().map(|entry| entry.snapshot.cycles)
+    }
+    /* Instruction count of the oldest snapshot */
+    pub fn oldest_cycles(&self) -> Option<u64> {
+        self.entries.front().map(|entry| entry.snapshot.cycles)
+    }
+    /* Adds a snapshot, dropping the oldest one when full */
+    pub fn push(&mut self, mut snapshot: Snapshot) {
+        if self.capacity == 0 {
+            return;
+        }
+        /* RAM of another size can't be diffed, e.g. after a platform change */
+        if self.ram.len() != snapshot.ram.len() {
+            self.clear();
+            self.ram = snapshot.ram.clone();
+        }
+        let ram_undo = self
+            .ram
+            .iter_mut()
+            .zip(&snapshot.ram)
+            .enumerate()
+            .filter(|(_, (old, new))| old != new)
+            .map(|(addr, (old, &new))| (addr as u16, std::mem::replace(old, new)))
+            .collect();
+        snapshot.ram = Vec::new();
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(RewindEntry { snapshot, ram_undo });
+    }
+    /* Removes and returns the newest snapshot */
+    pub fn pop(&mut self) -> Option<Snapshot> {
+        let RewindEntry {
+            mut snapshot,
+            ram_undo,
+        } = self.entries.pop_back()?;
+        snapshot.ram = self.ram.clone();
+        for (addr, value) in ram_undo {
+            self.ram[addr as usize] = value;
+        }
+        Some(snapshot)
+    }
+}
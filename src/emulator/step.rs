@@ -0,0 +1,36 @@
+/* Outcome of executing a single instruction */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepResult {
+    /* Raw opcode that was executed */
+    pub opcode: u16,
+    /* PC the opcode was fetched from */
+    pub pc_before: u16,
+    /* PC after execution, including jumps and skips */
+    pub pc_after: u16,
+    /* Whether the instruction changed the pixel buffer */
+    pub screen_changed: bool,
+    /* Whether the sound timer is running after execution */
+    pub sound_active: bool,
+}
+
+/* Outcome of executing a batch of instructions, see CHIP8::run_cycles and CHIP8::run_frame */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunResult {
+    /* Number of instructions executed */
+    pub cycles: usize,
+    /* Last instruction executed, if any */
+    pub last_step: Option<StepResult>,
+    /* Whether any instruction changed the pixel buffer */
+    pub screen_changed: bool,
+    /* Whether the sound timer is running at the end of the batch */
+    pub sound_active: bool,
+}
+impl RunResult {
+    /* Accumulates a single step into the batch result */
+    pub fn push(&mut self, step: StepResult) {
+        self.cycles += 1;
+        self.screen_changed |= step.screen_changed;
+        self.sound_active = step.sound_active;
+        self.last_step = Some(step);
+    }
+}
@@ -0,0 +1,241 @@
+use crate::emulator::CPU;
+use crate::emulator::mnemonics::{OpcodeClass, decode};
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::ops::RangeInclusive;
+
+/* Entries kept by the in-memory sink when no capacity is given */
+pub const DEFAULT_TRACE_CAPACITY: usize = 1024;
+
+/* Register whose value an instruction changed */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Register {
+    V(u8),
+    I,
+    SP,
+    DT,
+    ST,
+}
+impl std::str::FromStr for Register {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "I" => Ok(Register::I),
+            "SP" => Ok(Register::SP),
+            "DT" => Ok(Register::DT),
+            "ST" => Ok(Register::ST),
+            name => name
+                .strip_prefix('V')
+                .filter(|x| x.len() == 1)
+                .and_then(|x| u8::from_str_radix(x, 16).ok())
+                .map(Register::V)
+                .ok_or_else(|| format!("unknown register '{s}', expected V0-VF, I, SP, DT or ST")),
+        }
+    }
+}
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Register::V(x) => write!(f, "V{x:X}"),
+            Register::I => write!(f, "I"),
+            Register::SP => write!(f, "SP"),
+            Register::DT => write!(f, "DT"),
+            Register::ST => write!(f, "ST"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegisterDelta {
+    pub register: Register,
+    pub before: u16,
+    pub after: u16,
+}
+
+/* Registers compared before and after an instruction, PC is traced separately */
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Registers {
+    pub V: [u8; 16],
+    pub I: u16,
+    pub SP: u8,
+    pub DT: u8,
+    pub ST: u8,
+}
+impl Registers {
+    pub fn capture(cpu: &CPU) -> Registers {
+        Registers {
+            V: cpu.V,
+            I: cpu.I,
+            SP: cpu.STACK_POINTER,
+            DT: cpu.D_TIMER,
+            ST: cpu.S_TIMER,
+        }
+    }
+    /* Value of a single register */
+    pub fn get(&self, register: Register) -> u16 {
+        match register {
+            Register::V(x) => self.V[x as usize & 0xF] as u16,
+            Register::I => self.I,
+            Register::SP => self.SP as u16,
+            Register::DT => self.DT as u16,
+            Register::ST => self.ST as u16,
+        }
+    }
+    /* Registers that differ between two snapshots */
+    pub fn deltas(&self, after: &Registers) -> Vec<RegisterDelta> {
+        let mut deltas: Vec<RegisterDelta> = (0..16)
+            .filter(|&x| self.V[x] != after.V[x])
+            .map(|x| RegisterDelta {
+                register: Register::V(x as u8),
+                before: self.V[x] as u16,
+                after: after.V[x] as u16,
+            })
+            .collect();
+        let others = [
+            (Register::I, self.I, after.I),
+            (Register::SP, self.SP as u16, after.SP as u16),
+            (Register::DT, self.DT as u16, after.DT as u16),
+            (Register::ST, self.ST as u16, after.ST as u16),
+        ];
+        deltas.extend(
+            others
+                .into_iter()
+                .filter(|(_, before, after)| before != after)
+                .map(|(register, before, after)| RegisterDelta {
+                    register,
+                    before,
+                    after,
+                }),
+        );
+        deltas
+    }
+}
+
+/* A single traced instruction */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEntry {
+    /* Instructions executed before this one since the machine was created */
+    pub cycle: u64,
+    pub pc: u16,
+    pub opcode: u16,
+    /* Disassembled instruction */
+    pub text: String,
+    pub deltas: Vec<RegisterDelta>,
+}
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>10}  {:#06x}  {:04X}  {:<20}",
+            self.cycle, self.pc, self.opcode, self.text
+        )?;
+        for delta in &self.deltas {
+            match delta.register {
+                Register::I => write!(f, " I:{:04X}->{:04X}", delta.before, delta.after)?,
+                register => write!(f, " {register}:{:02X}->{:02X}", delta.before, delta.after)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/* Which instructions get traced, everything by default */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TraceFilter {
+    /* Only instructions fetched from this address range */
+    pub range: Option<RangeInclusive<u16>>,
+    /* Only instructions of these classes, all classes if empty */
+    pub classes: Vec<OpcodeClass>,
+}
+impl TraceFilter {
+    pub fn new() -> TraceFilter {
+        TraceFilter {
+            ..Default::default()
+        }
+    }
+    pub fn matches(&self, pc: u16, opcode: u16) -> bool {
+        if let Some(range) = &self.range
+            && !range.contains(&pc)
+        {
+            return false;
+        }
+        self.classes.is_empty() || self.classes.contains(&decode(opcode).class())
+    }
+}
+
+/* Where trace entries go */
+#[derive(Debug)]
+pub enum TraceSink {
+    Stderr,
+    File(BufWriter<File>),
+    /* Ring buffer keeping the last `capacity` entries */
+    Buffer {
+        entries: VecDeque<TraceEntry>,
+        capacity: usize,
+    },
+}
+impl TraceSink {
+    pub fn file<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<TraceSink> {
+        Ok(TraceSink::File(BufWriter::new(File::create(path)?)))
+    }
+    pub fn buffer(capacity: usize) -> TraceSink {
+        TraceSink::Buffer {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+}
+
+/* Opt-in instruction tracer, see CHIP8::set_tracer */
+#[derive(Debug)]
+pub struct Tracer {
+    pub sink: TraceSink,
+    pub filter: TraceFilter,
+}
+impl Tracer {
+    pub fn new(sink: TraceSink) -> Tracer {
+        Tracer {
+            sink,
+            filter: TraceFilter::new(),
+        }
+    }
+    pub fn with_filter(sink: TraceSink, filter: TraceFilter) -> Tracer {
+        Tracer { sink, filter }
+    }
+    #[inline]
+    pub fn matches(&self, pc: u16, opcode: u16) -> bool {
+        self.filter.matches(pc, opcode)
+    }
+    pub fn record(&mut self, entry: TraceEntry) -> std::io::Result<()> {
+        match &mut self.sink {
+            TraceSink::Stderr => writeln!(std::io::stderr().lock(), "{entry}"),
+            TraceSink::File(file) => writeln!(file, "{entry}"),
+            TraceSink::Buffer { entries, capacity } => {
+                if *capacity > 0 {
+                    if entries.len() == *capacity {
+                        entries.pop_front();
+                    }
+                    entries.push_back(entry);
+                }
+                Ok(())
+            }
+        }
+    }
+    /* Entries held by the in-memory sink, oldest first, empty for other sinks */
+    pub fn entries(&self) -> impl Iterator<Item = &TraceEntry> {
+        let entries = match &self.sink {
+            TraceSink::Buffer { entries, .. } => Some(entries.iter()),
+            _ => None,
+        };
+        entries.into_iter().flatten()
+    }
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        match &mut self.sink {
+            TraceSink::File(file) => file.flush(),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use crate::emulator::cpu::AUDIO_PATTERN_BYTES;
+use rodio::source::{SeekError, Source};
+use rodio::{OutputStream, OutputStreamBuilder, Sink, StreamError, source::SineWave};
+use std::time::Duration;
+
+pub struct Audio {
+    _stream_handle: OutputStream,
+    sink: Sink,
+    /* XO-CHIP audio pattern and pitch, the 440Hz beep is played until one is set */
+    pattern: Option<([u8; AUDIO_PATTERN_BYTES], u8)>,
+}
+
+impl Audio {
+    /* Opens the default output device, fails when no audio device is available */
+    pub fn new() -> Result<Self, StreamError> {
+        let _stream_handle = OutputStreamBuilder::open_default_stream()?;
+        let sink = rodio::Sink::connect_new(_stream_handle.mixer());
+
+        Ok(Self {
+            _stream_handle,
+            sink,
+            pattern: None,
+        })
+    }
+    #[inline]
+    pub fn set_pattern(&mut self, pattern: [u8; AUDIO_PATTERN_BYTES], pitch: u8) {
+        self.pattern = Some((pattern, pitch));
+    }
+    pub fn play_beep(&self) {
+        self.sink.stop();
+        match self.pattern {
+            Some((pattern, pitch)) => self.sink.append(PatternWave::new(pattern, pitch)),
+            None => self.sink.append(SineWave::new(440.0)),
+        }
+        self.sink.play();
+    }
+    #[inline]
+    pub fn stop_beep(&self) {
+        self.sink.stop();
+    }
+}
+
+/* Loops the 128 1-bit samples of an XO-CHIP audio pattern */
+struct PatternWave {
+    pattern: [u8; AUDIO_PATTERN_BYTES],
+    /* Pattern bits advanced per output sample */
+    step: f32,
+    position: f32,
+}
+impl PatternWave {
+    const SAMPLE_RATE: u32 = 48000;
+    const VOLUME: f32 = 0.25;
+
+    fn new(pattern: [u8; AUDIO_PATTERN_BYTES], pitch: u8) -> Self {
+        /* XO-CHIP playback rate: 4000 * 2^((pitch - 64) / 48) bits per second */
+        let rate = 4000.0 * 2f32.powf((f32::from(pitch) - 64.0) / 48.0);
+        Self {
+            pattern,
+            step: rate / Self::SAMPLE_RATE as f32,
+            position: 0.0,
+        }
+    }
+}
+impl Iterator for PatternWave {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let bit = self.position as usize % (AUDIO_PATTERN_BYTES * 8);
+        self.position = (self.position + self.step) % (AUDIO_PATTERN_BYTES * 8) as f32;
+        let on = self.pattern[bit / 8] & (0x80 >> (bit % 8)) != 0;
+        Some(if on { Self::VOLUME } else { -Self::VOLUME })
+    }
+}
+impl Source for PatternWave {
+    fn current_span_len(&self) -> Option<usize> {
+        None
+    }
+    fn channels(&self) -> rodio::ChannelCount {
+        1
+    }
+    fn sample_rate(&self) -> rodio::SampleRate {
+        Self::SAMPLE_RATE
+    }
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+    fn try_seek(&mut self, _position: Duration) -> Result<(), SeekError> {
+        Err(SeekError::NotSupported {
+            underlying_source: std::any::type_name::<Self>(),
+        })
+    }
+}
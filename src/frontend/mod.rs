@@ -0,0 +1,56 @@
+#[cfg(feature = "frontend")]
+pub mod audio;
+pub mod movie;
+pub mod null;
+#[cfg(feature = "frontend")]
+pub mod window;
+
+#[cfg(feature = "frontend")]
+pub use audio::Audio;
+pub use movie::{Movie, PlaybackFrontend, RecordingFrontend};
+pub use null::NullFrontend;
+#[cfg(feature = "frontend")]
+pub use window::MinifbFrontend;
+
+use crate::emulator::cpu::AUDIO_PATTERN_BYTES;
+use crate::emulator::{Display, Keypad};
+
+/* Emulator controls requested by the frontend, e.g. from keyboard shortcuts */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hotkey {
+    /* Increase instructions per frame */
+    SpeedUp,
+    /* Decrease instructions per frame */
+    SpeedDown,
+    /* Go back one recorded frame, sent every frame while held */
+    Rewind,
+    /* Save or load the state in a numbered slot */
+    SaveState(u8),
+    LoadState(u8),
+    /* Save the current frame as a PNG */
+    Screenshot,
+    /* Start or stop capturing a GIF clip */
+    Record,
+}
+
+/* Video, audio and input backend driven by CHIP8::run */
+pub trait Frontend {
+    /* Presents the display pixel buffer, once per frame */
+    fn present(&mut self, display: &Display);
+    /* Updates keypad with the current input state */
+    fn poll_input(&mut self, keypad: &mut Keypad);
+    /* Starts the beeper tone */
+    fn start_tone(&mut self);
+    /* Stops the beeper tone */
+    fn stop_tone(&mut self);
+    /* Sets the XO-CHIP audio pattern and pitch played instead of the default beep */
+    fn set_audio_pattern(&mut self, _pattern: &[u8; AUDIO_PATTERN_BYTES], _pitch: u8) {}
+    /* Returns if the emulation loop should stop */
+    fn should_quit(&self) -> bool;
+    /* Returns hotkeys triggered since the last poll */
+    fn poll_hotkeys(&mut self) -> Vec<Hotkey> {
+        Vec::new()
+    }
+    /* Sets the window title, if the frontend has one */
+    fn set_title(&mut self, _title: &str) {}
+}
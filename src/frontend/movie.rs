@@ -0,0 +1,366 @@
+use crate::CHIP8;
+use crate::emulator::cpu::AUDIO_PATTERN_BYTES;
+use crate::emulator::errors::{EmuError, ErrorPolicy, MovieError};
+use crate::emulator::keyboard::NUM_KEYS;
+use crate::emulator::savestate::rom_hash;
+use crate::emulator::{Display, Keypad, Platform, Rng, RngAlgorithm};
+use crate::frontend::{Frontend, Hotkey};
+use std::fmt::Write as _;
+use std::path::Path;
+
+pub const MOVIE_VERSION: u16 = 1;
+/* Longest movie accepted when parsing, a day of play at 60 frames per second */
+pub const MAX_MOVIE_FRAMES: usize = 24 * 60 * 60 * 60;
+
+/*
+    Keypad state of every frame plus everything else a run depends on, so it
+    replays bit-exactly. Stored as text, one frame per line as a hexadecimal
+    key mask (bit N set when key N is down), runs of equal frames as MASK*COUNT:
+
+        version 1
+        emulator 0.1.0
+        rom 7a83b63ba14b0d60
+        platform chip8
+        speed 12
+        timer-hz 60
+        on-error halt
+        rng xorshift
+        seed 1234
+        final-hash 5d1f7c0b3e9a4b21
+        frames
+        0000*120
+        0010*3
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Movie {
+    /* Version of the emulator that recorded the movie */
+    pub emulator: String,
+    pub rom_hash: u64,
+    pub platform: Platform,
+    pub cycles_per_frame: usize,
+    pub timer_hz: u32,
+    pub error_policy: ErrorPolicy,
+    pub rng: RngAlgorithm,
+    pub seed: u64,
+    /* Display::frame_hash after the last frame, if known */
+    pub final_hash: Option<u64>,
+    /* Key masks, one per frame */
+    pub frames: Vec<u16>,
+}
+impl Movie {
+    /* Empty movie for the machine's ROM and settings, resets it and reseeds its RNG with `seed` */
+    pub fn record(chip8: &mut CHIP8, seed: u64) -> Movie {
+        let rng = chip8.rng().algorithm();
+        chip8.set_rng(Rng::with_algorithm(rng, seed));
+        chip8.reset();
+        Movie {
+            emulator: env!("CARGO_PKG_VERSION").to_string(),
+            rom_hash: rom_hash(chip8.rom()),
+            platform: chip8.platform(),
+            cycles_per_frame: chip8.cycles_per_frame(),
+            timer_hz: chip8.timer_hz(),
+            error_policy: chip8.error_policy(),
+            rng,
+            seed,
+            final_hash: None,
+            frames: Vec::new(),
+        }
+    }
+    /* Configures and resets the machine as it was when recording started */
+    pub fn prepare(&self, chip8: &mut CHIP8) -> Result<(), EmuError> {
+        let expected = rom_hash(chip8.rom());
+        if self.rom_hash != expected {
+            return Err(MovieError::RomMismatch {
+                expected,
+                found: self.rom_hash,
+            }
+            .into());
+        }
+        if chip8.platform() != self.platform {
+            chip8.set_platform(self.platform)?;
+        }
+        chip8.set_cycles_per_frame(self.cycles_per_frame);
+        chip8.set_timer_hz(self.timer_hz);
+        chip8.set_error_policy(self.error_policy);
+        chip8.set_rng(Rng::with_algorithm(self.rng, self.seed));
+        chip8.reset();
+        Ok(())
+    }
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+    pub fn push(&mut self, keypad: &Keypad) {
+        let mask = (0..NUM_KEYS)
+            .filter(|&key| keypad.is_key_down(key))
+            .fold(0u16, |mask, key| mask | 1 << key);
+        self.frames.push(mask);
+    }
+    /* Sets the keypad to its state in `frame` */
+    pub fn apply(&self, frame: usize, keypad: &mut Keypad) {
+        let mask = self.frames.get(frame).copied().unwrap_or(0);
+        for key in 0..NUM_KEYS {
+            keypad.set_key(key, mask & 1 << key != 0);
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Movie, MovieError> {
+        std::fs::read_to_string(path)?.parse()
+    }
+}
+impl std::fmt::Display for Movie {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "version {MOVIE_VERSION}")?;
+        writeln!(f, "emulator {}", self.emulator)?;
+        writeln!(f, "rom {:016x}", self.rom_hash)?;
+        writeln!(f, "platform {}", self.platform)?;
+        writeln!(f, "speed {}", self.cycles_per_frame)?;
+        writeln!(f, "timer-hz {}", self.timer_hz)?;
+        writeln!(f, "on-error {}", self.error_policy)?;
+        writeln!(f, "rng {}", self.rng)?;
+        writeln!(f, "seed {}", self.seed)?;
+        if let Some(hash) = self.final_hash {
+            writeln!(f, "final-hash {hash:016x}")?;
+        }
+        writeln!(f, "frames")?;
+        let mut frames = self.frames.iter().peekable();
+        while let Some(&mask) = frames.next() {
+            let mut count = 1;
+            while frames.next_if_eq(&&mask).is_some() {
+                count += 1;
+            }
+            let mut line = format!("{mask:04x}");
+            if count > 1 {
+                let _ = write!(line, "*{count}");
+            }
+            writeln!(f, "{line}")?;
+        }
+        Ok(())
+    }
+}
+impl std::str::FromStr for Movie {
+    type Err = MovieError;
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+        let mut headers = std::collections::HashMap::new();
+        for (number, line) in lines.by_ref() {
+            if line == "frames" {
+                break;
+            }
+            let (key, value) = line
+                .split_once(' ')
+                .ok_or_else(|| syntax(number, "expected 'key value'"))?;
+            headers.insert(key, (number, value.trim()));
+        }
+        let header = |key: &'static str| {
+            headers
+                .get(key)
+                .copied()
+                .ok_or(MovieError::MissingHeader(key))
+        };
+        fn value<T: std::str::FromStr>((line, value): (usize, &str)) -> Result<T, MovieError> {
+            value
+                .parse()
+                .map_err(|_| syntax(line, &format!("invalid value '{value}'")))
+        }
+        fn hex((line, value): (usize, &str)) -> Result<u64, MovieError> {
+            u64::from_str_radix(value, 16)
+                .map_err(|_| syntax(line, &format!("invalid hash '{value}'")))
+        }
+
+        let version: u16 = value(header("version")?)?;
+        if version != MOVIE_VERSION {
+            return Err(MovieError::Version {
+                found: version,
+                expected: MOVIE_VERSION,
+            });
+        }
+        let mut movie = Movie {
+            emulator: header("emulator")?.1.to_string(),
+            rom_hash: hex(header("rom")?)?,
+            platform: value(header("platform")?)?,
+            cycles_per_frame: value(header("speed")?)?,
+            timer_hz: value(header("timer-hz")?)?,
+            error_policy: value(header("on-error")?)?,
+            rng: value(header("rng")?)?,
+            seed: value(header("seed")?)?,
+            final_hash: headers.get("final-hash").copied().map(hex).transpose()?,
+            frames: Vec::new(),
+        };
+        for (number, line) in lines {
+            let (mask, count) = line.split_once('*').unwrap_or((line, "1"));
+            let mask = u16::from_str_radix(mask, 16)
+                .map_err(|_| syntax(number, &format!("invalid key mask '{mask}'")))?;
+            let count: usize = value((number, count))?;
+            if count > MAX_MOVIE_FRAMES - movie.frames.len() {
+                return Err(syntax(
+                    number,
+                    &format!("movie is longer than {MAX_MOVIE_FRAMES} frames"),
+                ));
+            }
+            movie.frames.extend(std::iter::repeat_n(mask, count));
+        }
+        Ok(movie)
+    }
+}
+
+fn syntax(line: usize, message: &str) -> MovieError {
+    MovieError::Syntax {
+        line,
+        message: message.to_string(),
+    }
+}
+
+/* Hotkeys that would make the run diverge from its input, ignored while a movie records or plays */
+fn desyncs(hotkey: &Hotkey) -> bool {
+    matches!(
+        hotkey,
+        Hotkey::SpeedUp | Hotkey::SpeedDown | Hotkey::Rewind | Hotkey::LoadState(_)
+    )
+}
+
+/* Wraps a frontend and records the keypad state it reports every frame */
+pub struct RecordingFrontend<F: Frontend> {
+    inner: F,
+    movie: Movie,
+}
+impl<F: Frontend> RecordingFrontend<F> {
+    pub fn new(inner: F, movie: Movie) -> Self {
+        Self { inner, movie }
+    }
+    #[inline]
+    pub fn movie(&self) -> &Movie {
+        &self.movie
+    }
+    pub fn into_movie(self) -> Movie {
+        self.movie
+    }
+}
+impl<F: Frontend> Frontend for RecordingFrontend<F> {
+    fn present(&mut self, display: &Display) {
+        self.movie.final_hash = Some(display.frame_hash());
+        self.inner.present(display);
+    }
+    fn poll_input(&mut self, keypad: &mut Keypad) {
+        self.inner.poll_input(keypad);
+        self.movie.push(keypad);
+    }
+    fn start_tone(&mut self) {
+        self.inner.start_tone();
+    }
+    fn stop_tone(&mut self) {
+        self.inner.stop_tone();
+    }
+    fn set_audio_pattern(&mut self, pattern: &[u8; AUDIO_PATTERN_BYTES], pitch: u8) {
+        self.inner.set_audio_pattern(pattern, pitch);
+    }
+    fn should_quit(&self) -> bool {
+        self.inner.should_quit()
+    }
+    fn poll_hotkeys(&mut self) -> Vec<Hotkey> {
+        let mut hotkeys = self.inner.poll_hotkeys();
+        hotkeys.retain(|hotkey| !desyncs(hotkey));
+        hotkeys
+    }
+    fn set_title(&mut self, title: &str) {
+        self.inner.set_title(&format!("{title} | recording"));
+    }
+}
+
+/* Wraps a frontend and feeds a movie's keypad states instead of its input, live input resumes after the last frame */
+pub struct PlaybackFrontend<F: Frontend> {
+    inner: F,
+    movie: Movie,
+    /* Frames played so far */
+    frame: usize,
+    /* Quit after the last frame instead of handing over to live input */
+    quit_at_end: bool,
+    /* Display::frame_hash of the last presented frame */
+    last_hash: Option<u64>,
+}
+impl<F: Frontend> PlaybackFrontend<F> {
+    pub fn new(inner: F, movie: Movie) -> Self {
+        Self {
+            inner,
+            movie,
+            frame: 0,
+            quit_at_end: false,
+            last_hash: None,
+        }
+    }
+    /* Playback that stops the emulator after the last frame */
+    pub fn until_end(inner: F, movie: Movie) -> Self {
+        Self {
+            quit_at_end: true,
+            ..Self::new(inner, movie)
+        }
+    }
+    #[inline]
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+    #[inline]
+    pub fn finished(&self) -> bool {
+        self.frame >= self.movie.len()
+    }
+    #[inline]
+    pub fn last_hash(&self) -> Option<u64> {
+        self.last_hash
+    }
+    #[inline]
+    pub fn movie(&self) -> &Movie {
+        &self.movie
+    }
+}
+impl<F: Frontend> Frontend for PlaybackFrontend<F> {
+    fn present(&mut self, display: &Display) {
+        self.last_hash = Some(display.frame_hash());
+        self.inner.present(display);
+    }
+    fn poll_input(&mut self, keypad: &mut Keypad) {
+        if self.finished() {
+            self.inner.poll_input(keypad);
+            return;
+        }
+        /* Keep the inner frontend's input handling running, but discard its keys */
+        self.inner.poll_input(&mut Keypad::new());
+        self.movie.apply(self.frame, keypad);
+        self.frame += 1;
+    }
+    fn start_tone(&mut self) {
+        self.inner.start_tone();
+    }
+    fn stop_tone(&mut self) {
+        self.inner.stop_tone();
+    }
+    fn set_audio_pattern(&mut self, pattern: &[u8; AUDIO_PATTERN_BYTES], pitch: u8) {
+        self.inner.set_audio_pattern(pattern, pitch);
+    }
+    fn should_quit(&self) -> bool {
+        self.inner.should_quit() || (self.quit_at_end && self.finished())
+    }
+    fn poll_hotkeys(&mut self) -> Vec<Hotkey> {
+        let mut hotkeys = self.inner.poll_hotkeys();
+        if !self.finished() {
+            hotkeys.retain(|hotkey| !desyncs(hotkey));
+        }
+        hotkeys
+    }
+    fn set_title(&mut self, title: &str) {
+        match self.finished() {
+            true => self.inner.set_title(title),
+            false => self.inner.set_title(&format!("{title} | playback")),
+        }
+    }
+}
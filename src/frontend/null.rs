@@ -0,0 +1,59 @@
+use crate::emulator::cpu::AUDIO_PATTERN_BYTES;
+use crate::emulator::keyboard::NUM_KEYS;
+use crate::emulator::{Display, Keypad};
+use crate::frontend::Frontend;
+
+/* In-memory frontend without any window or audio device, used for tests and automation */
+#[derive(Debug, Default)]
+pub struct NullFrontend {
+    /* Number of frames presented so far */
+    pub frames: usize,
+    /* Quit once this many frames have been presented */
+    pub max_frames: Option<usize>,
+    /* Copy of the last presented pixel buffer */
+    pub last_frame: Vec<u32>,
+    /* Key state applied to the keypad on every poll */
+    pub keys: [bool; NUM_KEYS],
+    /* Whether the beeper tone is currently on */
+    pub tone: bool,
+    /* Last XO-CHIP audio pattern and pitch received */
+    pub audio_pattern: Option<([u8; AUDIO_PATTERN_BYTES], u8)>,
+}
+impl NullFrontend {
+    pub fn new() -> Self {
+        Self {
+            ..Default::default()
+        }
+    }
+    /* Frontend that quits after presenting `frames` frames */
+    pub fn with_max_frames(frames: usize) -> Self {
+        Self {
+            max_frames: Some(frames),
+            ..Default::default()
+        }
+    }
+}
+impl Frontend for NullFrontend {
+    fn present(&mut self, display: &Display) {
+        self.last_frame.clear();
+        self.last_frame.extend_from_slice(&display.pixels_buffer);
+        self.frames += 1;
+    }
+    fn poll_input(&mut self, keypad: &mut Keypad) {
+        for (key, &pressed) in self.keys.iter().enumerate() {
+            keypad.set_key(key, pressed);
+        }
+    }
+    fn start_tone(&mut self) {
+        self.tone = true;
+    }
+    fn stop_tone(&mut self) {
+        self.tone = false;
+    }
+    fn set_audio_pattern(&mut self, pattern: &[u8; AUDIO_PATTERN_BYTES], pitch: u8) {
+        self.audio_pattern = Some((*pattern, pitch));
+    }
+    fn should_quit(&self) -> bool {
+        self.max_frames.is_some_and(|max| self.frames >= max)
+    }
+}
@@ -0,0 +1,192 @@
+use crate::FRAME_RATE;
+use crate::emulator::KeyboardError;
+use crate::emulator::cpu::AUDIO_PATTERN_BYTES;
+use crate::emulator::display::{Display, WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::emulator::keyboard::{KEY_NOT_PRESSED, KEY_PRESSED, Keypad};
+use crate::emulator::savestate::NUM_SAVE_SLOTS;
+use crate::frontend::{Audio, Frontend, Hotkey};
+use minifb::{Key, KeyRepeat, Scale, WindowOptions};
+
+/*
+            CHIP-8 keypad layout:
+                1 2 3 C
+                4 5 6 D
+                7 8 9 E
+                A 0 B F
+
+            Mapped to PC keyboard:
+                1 2 3 4
+                Q W E R
+                A S D F
+                Z X C V
+*/
+
+pub const KEYMAP: [(Key, usize); 16] = [
+    (Key::Key1, 0x1),
+    (Key::Key2, 0x2),
+    (Key::Key3, 0x3),
+    (Key::Key4, 0xC),
+    (Key::Q, 0x4),
+    (Key::W, 0x5),
+    (Key::E, 0x6),
+    (Key::R, 0xD),
+    (Key::A, 0x7),
+    (Key::S, 0x8),
+    (Key::D, 0x9),
+    (Key::F, 0xE),
+    (Key::Z, 0xA),
+    (Key::X, 0x0),
+    (Key::C, 0xB),
+    (Key::V, 0xF),
+];
+/*
+            Hotkeys:
+                -  Decrease speed
+                =  Increase speed
+                Backspace (held)  Rewind
+                F1-F9  Load state slot 1-9
+                Shift+F1-F9  Save state slot 1-9
+                F11  Start/stop GIF capture
+                F12  Screenshot
+*/
+pub const HOTKEYS: [(Key, Hotkey); 2] = [
+    (Key::Minus, Hotkey::SpeedDown),
+    (Key::Equal, Hotkey::SpeedUp),
+];
+
+/* Held down rather than pressed, rewinds one frame per frame */
+pub const REWIND_KEY: Key = Key::Backspace;
+
+/* Not repeated while held, one screenshot per press */
+pub const SCREENSHOT_KEY: Key = Key::F12;
+
+/* Toggles a GIF capture, not repeated while held */
+pub const RECORD_KEY: Key = Key::F11;
+
+/* Save state slots 1-9 */
+pub const SLOT_KEYS: [Key; NUM_SAVE_SLOTS as usize] = [
+    Key::F1,
+    Key::F2,
+    Key::F3,
+    Key::F4,
+    Key::F5,
+    Key::F6,
+    Key::F7,
+    Key::F8,
+    Key::F9,
+];
+
+/* PC Keyboard Key mapped to CHIP-8 Keypad Key */
+pub fn key_to_chip8(key: Key) -> Result<usize, KeyboardError> {
+    for (pc_key, chip8_key) in KEYMAP {
+        if pc_key == key {
+            return Ok(chip8_key);
+        }
+    }
+    Err(KeyboardError::UnknownKey)
+}
+
+/* minifb window with rodio audio, the default desktop frontend */
+pub struct MinifbFrontend {
+    window: minifb::Window,
+    /* None if no audio device is available, the emulator then runs silently */
+    audio: Option<Audio>,
+}
+impl Default for MinifbFrontend {
+    fn default() -> MinifbFrontend {
+        let mut window = minifb::Window::new(
+            "CHIP-8",
+            WINDOW_WIDTH,
+            WINDOW_HEIGHT,
+            WindowOptions {
+                scale: Scale::X16,
+                ..WindowOptions::default()
+            },
+        )
+        .expect("Failed to create window.");
+        /* Limit FPS to the display refresh rate */
+        window.set_target_fps(FRAME_RATE as usize);
+        MinifbFrontend {
+            window,
+            audio: Audio::new().ok(),
+        }
+    }
+}
+impl MinifbFrontend {
+    pub fn new() -> Self {
+        Self {
+            ..Default::default()
+        }
+    }
+}
+impl Frontend for MinifbFrontend {
+    /* Updates window with the display pixel buffer */
+    fn present(&mut self, display: &Display) {
+        self.window
+            .update_with_buffer(
+                &display.pixels_buffer,
+                display.buffer_width(),
+                display.buffer_height(),
+            )
+            .unwrap();
+    }
+    /* Update keypad with key state of the PC keyboard */
+    fn poll_input(&mut self, keypad: &mut Keypad) {
+        for (pc_key, chip8_key) in KEYMAP {
+            let pressed = if self.window.is_key_down(pc_key) {
+                KEY_PRESSED
+            } else {
+                KEY_NOT_PRESSED
+            };
+            keypad.set_key(chip8_key, pressed);
+        }
+    }
+    fn start_tone(&mut self) {
+        if let Some(audio) = &self.audio {
+            audio.play_beep();
+        }
+    }
+    fn set_audio_pattern(&mut self, pattern: &[u8; AUDIO_PATTERN_BYTES], pitch: u8) {
+        if let Some(audio) = &mut self.audio {
+            audio.set_pattern(*pattern, pitch);
+        }
+    }
+    fn stop_tone(&mut self) {
+        if let Some(audio) = &self.audio {
+            audio.stop_beep();
+        }
+    }
+    fn should_quit(&self) -> bool {
+        !self.window.is_open()
+    }
+    fn poll_hotkeys(&mut self) -> Vec<Hotkey> {
+        let mut hotkeys: Vec<Hotkey> = HOTKEYS
+            .iter()
+            .filter(|(pc_key, _)| self.window.is_key_pressed(*pc_key, KeyRepeat::Yes))
+            .map(|&(_, hotkey)| hotkey)
+            .collect();
+        if self.window.is_key_down(REWIND_KEY) {
+            hotkeys.push(Hotkey::Rewind);
+        }
+        if self.window.is_key_pressed(SCREENSHOT_KEY, KeyRepeat::No) {
+            hotkeys.push(Hotkey::Screenshot);
+        }
+        if self.window.is_key_pressed(RECORD_KEY, KeyRepeat::No) {
+            hotkeys.push(Hotkey::Record);
+        }
+        let shift =
+            self.window.is_key_down(Key::LeftShift) || self.window.is_key_down(Key::RightShift);
+        for (slot, &key) in (1..).zip(SLOT_KEYS.iter()) {
+            if self.window.is_key_pressed(key, KeyRepeat::No) {
+                hotkeys.push(match shift {
+                    true => Hotkey::SaveState(slot),
+                    false => Hotkey::LoadState(slot),
+                });
+            }
+        }
+        hotkeys
+    }
+    fn set_title(&mut self, title: &str) {
+        self.window.set_title(title);
+    }
+}
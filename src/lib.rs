@@ -1,16 +1,70 @@
+pub mod capture;
 pub mod cli;
+pub mod debugger;
 pub mod emulator;
+pub mod frontend;
+pub mod runner;
 
-use crate::emulator::errors::EmuError;
-use crate::emulator::{Audio, CPU, Memory, display::Display, keyboard::Keypad};
+use crate::capture::{DEFAULT_SCREENSHOT_SCALE, Recorder, Tone, capture_path, save_screenshot};
+use crate::emulator::errors::{EmuError, ErrorPolicy, SaveStateError};
+use crate::emulator::savestate::{SAVE_STATE_VERSION, rom_hash};
+use crate::emulator::{
+    CPU, CrashReport, History, Memory, Platform, Quirks, RewindBuffer, Rng, RunResult, SaveState,
+    SaveStateHeader, Snapshot, StepResult, TraceEntry, Tracer, disassemble, display::Display,
+    keyboard::Keypad, trace::Registers,
+};
+use crate::frontend::{Frontend, Hotkey};
+use std::path::{Path, PathBuf};
+
+/* Display refresh rate, one frame per presented image */
+pub const FRAME_RATE: u32 = 60;
+pub const DEFAULT_CYCLES_PER_FRAME: usize = 12;
+pub const DEFAULT_TIMER_HZ: u32 = 60;
+pub const MIN_CYCLES_PER_FRAME: usize = 1;
+pub const MAX_CYCLES_PER_FRAME: usize = 1000;
+pub const MIN_TIMER_HZ: u32 = 1;
+pub const MAX_TIMER_HZ: u32 = 1000;
+/* Seconds of gameplay kept for rewinding */
+pub const DEFAULT_REWIND_SECONDS: usize = 10;
+pub const MAX_REWIND_SECONDS: usize = 600;
+pub const DEFAULT_REWIND_FRAMES: usize = DEFAULT_REWIND_SECONDS * FRAME_RATE as usize;
 
 #[allow(non_snake_case)]
+#[derive(Debug)]
 pub struct CHIP8 {
     cpu: CPU,
     memory: Memory,
     display: Display,
-    audio: Audio,
     keypad: Keypad,
+    /* Platform profile the emulator was configured for */
+    platform: Platform,
+    /* ROM loaded into RAM, kept to reload it on reset */
+    rom: Vec<u8>,
+    /* File the ROM was loaded from, save slots are stored next to it */
+    rom_path: Option<PathBuf>,
+    /* Instructions executed per frame */
+    cycles_per_frame: usize,
+    /* Delay and sound timer frequency */
+    timer_hz: u32,
+    /* Timer ticks owed to the timers, in 1/FRAME_RATE units */
+    timer_accumulator: u32,
+    /* Last executed instructions, for crash reports */
+    history: History,
+    /* Instructions executed since the machine was created */
+    cycles: u64,
+    /* Instruction tracer, None when tracing is disabled */
+    tracer: Option<Tracer>,
+    /* Per-frame snapshots for the rewind hotkey */
+    rewind: RewindBuffer,
+    /* Integer scale of screenshots and clips taken with the hotkeys */
+    screenshot_scale: usize,
+    /* Video and audio capture fed by run, None when not capturing */
+    capture: Option<Recorder>,
+}
+impl Default for CHIP8 {
+    fn default() -> CHIP8 {
+        CHIP8::new()
+    }
 }
 impl CHIP8 {
     pub fn new() -> CHIP8 {
@@ -18,57 +72,616 @@ impl CHIP8 {
             cpu: CPU::new(),
             memory: Memory::new(),
             display: Display::new(),
-            audio: Audio::new(),
             keypad: Keypad::new(),
+            platform: Platform::Chip8,
+            rom: Vec::new(),
+            rom_path: None,
+            cycles_per_frame: DEFAULT_CYCLES_PER_FRAME,
+            timer_hz: DEFAULT_TIMER_HZ,
+            timer_accumulator: 0,
+            history: History::new(),
+            cycles: 0,
+            tracer: None,
+            rewind: RewindBuffer::new(DEFAULT_REWIND_FRAMES),
+            screenshot_scale: DEFAULT_SCREENSHOT_SCALE,
+            capture: None,
         }
     }
+    pub fn with_platform(platform: Platform) -> CHIP8 {
+        let mut chip8 = CHIP8::new();
+        chip8.platform = platform;
+        chip8.apply_platform();
+        chip8
+    }
+    /* Reconfigures CPU quirks, speed, memory and display for a platform, then reloads the ROM */
+    pub fn set_platform(&mut self, platform: Platform) -> Result<(), EmuError> {
+        self.platform = platform;
+        self.apply_platform();
+        self.memory.load(&self.rom)?;
+        Ok(())
+    }
+    fn apply_platform(&mut self) {
+        let (width, height) = self.platform.resolution();
+        let error_policy = self.cpu.error_policy;
+        let rng = self.cpu.rng;
+        self.cpu = CPU::with_quirks(self.platform.quirks());
+        self.cpu.instruction_set = self.platform.instruction_set();
+        self.cpu.error_policy = error_policy;
+        self.cpu.rng = rng;
+        self.memory = Memory::with_size(self.platform.ram_size());
+        self.display = Display::with_resolution(width, height);
+        self.keypad.reset();
+        self.cycles_per_frame = self.platform.cycles_per_frame();
+        self.timer_accumulator = 0;
+        self.history.clear();
+        self.rewind.clear();
+    }
+    /* Resets the machine and reloads the current ROM */
     pub fn reset(&mut self) {
         self.cpu.reset();
         self.memory.reset();
-        self.display.clear();
+        self.display.reset();
         self.keypad.reset();
+        self.timer_accumulator = 0;
+        self.history.clear();
+        self.rewind.clear();
+        /* The ROM fitted before, so reloading it cannot fail */
+        let _ = self.memory.load(&self.rom);
     }
-    pub fn load<P: AsRef<std::path::Path>>(&mut self, rom: P) -> Result<(), EmuError> {
+    pub fn load<P: AsRef<Path>>(&mut self, rom: P) -> Result<(), EmuError> {
         use std::io::Read;
-        let mut file = std::fs::File::open(rom)?;
+        let mut file = std::fs::File::open(&rom)?;
         let mut buffer = Vec::new();
         file.read_to_end(&mut buffer)?;
-        self.memory.load(&buffer)?;
+        self.load_bytes(buffer)?;
+        self.rom_path = Some(rom.as_ref().to_path_buf());
+        Ok(())
+    }
+    /* Loads a ROM image already in memory */
+    pub fn load_bytes<R: Into<Vec<u8>>>(&mut self, rom: R) -> Result<(), EmuError> {
+        let rom = rom.into();
+        self.memory.load(&rom)?;
+        self.rom = rom;
+        self.rom_path = None;
+        Ok(())
+    }
+    #[inline]
+    pub fn platform(&self) -> Platform {
+        self.platform
+    }
+    #[inline]
+    pub fn rom(&self) -> &[u8] {
+        &self.rom
+    }
+    #[inline]
+    pub fn cpu(&self) -> &CPU {
+        &self.cpu
+    }
+    /* Direct register access, for debuggers */
+    #[inline]
+    pub fn cpu_mut(&mut self) -> &mut CPU {
+        &mut self.cpu
+    }
+    #[inline]
+    pub fn memory(&self) -> &Memory {
+        &self.memory
+    }
+    #[inline]
+    pub fn memory_mut(&mut self) -> &mut Memory {
+        &mut self.memory
+    }
+    #[inline]
+    pub fn display(&self) -> &Display {
+        &self.display
+    }
+    /* Direct screen access, for setting up tests */
+    #[inline]
+    pub fn display_mut(&mut self) -> &mut Display {
+        &mut self.display
+    }
+    #[inline]
+    pub fn quirks(&self) -> Quirks {
+        self.cpu.quirks
+    }
+    #[inline]
+    pub fn set_quirks(&mut self, quirks: Quirks) {
+        self.cpu.quirks = quirks;
+    }
+    #[inline]
+    pub fn error_policy(&self) -> ErrorPolicy {
+        self.cpu.error_policy
+    }
+    /* Sets what happens on bad memory accesses, stack errors and unknown opcodes */
+    #[inline]
+    pub fn set_error_policy(&mut self, policy: ErrorPolicy) {
+        self.cpu.error_policy = policy;
+    }
+    #[inline]
+    pub fn rng(&self) -> Rng {
+        self.cpu.rng
+    }
+    /* Replaces the CXNN random number generator, e.g. Rng::from_seed for reproducible runs */
+    pub fn set_rng(&mut self, rng: Rng) {
+        self.cpu.rng = rng;
+    }
+    #[inline]
+    pub fn cycles_per_frame(&self) -> usize {
+        self.cycles_per_frame
+    }
+    /* Sets the instructions executed per frame, clamped to a sane range */
+    pub fn set_cycles_per_frame(&mut self, cycles: usize) {
+        self.cycles_per_frame = cycles.clamp(MIN_CYCLES_PER_FRAME, MAX_CYCLES_PER_FRAME);
+    }
+    #[inline]
+    pub fn timer_hz(&self) -> u32 {
+        self.timer_hz
+    }
+    /* Sets the delay and sound timer frequency, independent of the frame rate, clamped to a sane range */
+    pub fn set_timer_hz(&mut self, hz: u32) {
+        self.timer_hz = hz.clamp(MIN_TIMER_HZ, MAX_TIMER_HZ);
+    }
+    #[inline]
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+    #[inline]
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+    #[inline]
+    pub fn tracer(&self) -> Option<&Tracer> {
+        self.tracer.as_ref()
+    }
+    #[inline]
+    pub fn tracer_mut(&mut self) -> Option<&mut Tracer> {
+        self.tracer.as_mut()
+    }
+    /* Enables tracing with `Some`, disables it with `None`, returning the previous tracer */
+    pub fn set_tracer(&mut self, tracer: Option<Tracer>) -> Option<Tracer> {
+        std::mem::replace(&mut self.tracer, tracer)
+    }
+    #[inline]
+    pub fn keypad_mut(&mut self) -> &mut Keypad {
+        &mut self.keypad
+    }
+    /* Fetches and executes a single instruction */
+    pub fn step(&mut self) -> Result<StepResult, EmuError> {
+        let pc_before = self.cpu.PROGRAM_COUNTER;
+        self.display.take_changed();
+        let opcode = self
+            .cpu
+            .fetch(&self.memory)
+            .map_err(|error| EmuError::Cpu {
+                pc: pc_before,
+                /* The opcode itself could not be read */
+                opcode: 0,
+                source: error.into(),
+            })?;
+        self.history.push(pc_before, opcode);
+        /* Registers are only captured when the instruction is traced */
+        let traced = match &self.tracer {
+            Some(tracer) if tracer.matches(pc_before, opcode) => {
+                Some(Registers::capture(&self.cpu))
+            }
+            _ => None,
+        };
+        self.cpu
+            .decode_execute(
+                &mut self.memory,
+                &mut self.display,
+                &mut self.keypad,
+                opcode,
+            )
+            .map_err(|source| EmuError::Cpu {
+                pc: pc_before,
+                opcode,
+                source,
+            })?;
+        if let Some(before) = traced
+            && let Some(tracer) = &mut self.tracer
+        {
+            tracer.record(TraceEntry {
+                cycle: self.cycles,
+                pc: pc_before,
+                opcode,
+                text: disassemble(opcode),
+                deltas: before.deltas(&Registers::capture(&self.cpu)),
+            })?;
+        }
+        self.cycles += 1;
+        Ok(StepResult {
+            opcode,
+            pc_before,
+            pc_after: self.cpu.PROGRAM_COUNTER,
+            screen_changed: self.display.take_changed(),
+            sound_active: self.cpu.sound_active(),
+        })
+    }
+    /* Executes `cycles` instructions, without updating the timers */
+    pub fn run_cycles(&mut self, cycles: usize) -> Result<RunResult, EmuError> {
+        let mut result = RunResult {
+            sound_active: self.cpu.sound_active(),
+            ..Default::default()
+        };
+        for _ in 0..cycles {
+            result.push(self.step()?);
+        }
+        Ok(result)
+    }
+    /* Runs one frame, CPU cycles followed by the timer updates due in that frame */
+    pub fn run_frame(&mut self) -> Result<RunResult, EmuError> {
+        self.run_frame_until(|_| false).map(|(result, _)| result)
+    }
+    /*
+        Runs one frame like run_frame, but stops right after an instruction `stop`
+        returns true for. The timers only tick if the frame completes, the bool
+        tells whether it was stopped early
+    */
+    pub fn run_frame_until<S: FnMut(&StepResult) -> bool>(
+        &mut self,
+        mut stop: S,
+    ) -> Result<(RunResult, bool), EmuError> {
+        let mut result = RunResult::default();
+        for _ in 0..self.cycles_per_frame {
+            let step = self.step()?;
+            result.push(step);
+            if stop(&step) {
+                result.sound_active = self.cpu.sound_active();
+                return Ok((result, true));
+            }
+            /* Display wait quirk, a sprite draw ends the frame */
+            if std::mem::take(&mut self.cpu.vblank_wait) {
+                break;
+            }
+        }
+        self.tick_timers();
+        result.sound_active = self.cpu.sound_active();
+        Ok((result, false))
+    }
+    /* Captures the complete machine state */
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            cpu: self.cpu.clone(),
+            stack: self.memory.STACK.clone(),
+            ram: self.memory.RAM.clone(),
+            display: self.display.state(),
+            keypad: self.keypad,
+            timer_accumulator: self.timer_accumulator,
+            cycles: self.cycles,
+        }
+    }
+    /* Returns the machine to a captured state, keeping the error policy and any memory observer */
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        let access_log = self.cpu.access_log.take();
+        let error_policy = self.cpu.error_policy;
+        self.cpu = snapshot.cpu.clone();
+        self.cpu.access_log = access_log;
+        self.cpu.error_policy = error_policy;
+        self.memory.STACK.clone_from(&snapshot.stack);
+        self.memory.RAM.clone_from(&snapshot.ram);
+        self.display.restore(&snapshot.display);
+        self.keypad = snapshot.keypad;
+        self.timer_accumulator = snapshot.timer_accumulator;
+        self.cycles = snapshot.cycles;
+    }
+    /* Sets how many frames the rewind hotkey can go back, 0 disables recording */
+    pub fn set_rewind_frames(&mut self, frames: usize) {
+        self.rewind = RewindBuffer::new(frames);
+    }
+    #[inline]
+    pub fn rewind_frames(&self) -> usize {
+        self.rewind.len()
+    }
+    /* Records the state at the end of a frame for rewinding */
+    pub fn record_frame(&mut self) {
+        if self.rewind.capacity() > 0 {
+            let snapshot = self.snapshot();
+            self.rewind.push(snapshot);
+        }
+    }
+    /* Goes back to the previous recorded frame, returns false once the buffer is exhausted */
+    pub fn rewind(&mut self) -> bool {
+        while let Some(snapshot) = self.rewind.pop() {
+            /* The newest snapshot may be the current state itself */
+            if snapshot.cycles < self.cycles || self.rewind.is_empty() {
+                self.restore(&snapshot);
+                return true;
+            }
+        }
+        false
+    }
+    /* Captures the machine state with a header identifying the ROM, platform and emulator */
+    pub fn save_state(&self) -> SaveState {
+        SaveState {
+            header: SaveStateHeader {
+                version: SAVE_STATE_VERSION,
+                emulator: env!("CARGO_PKG_VERSION").to_string(),
+                platform: self.platform.name().to_string(),
+                rom_hash: rom_hash(&self.rom),
+            },
+            snapshot: self.snapshot(),
+        }
+    }
+    /* Restores a save state taken with the same ROM and platform */
+    pub fn load_state(&mut self, state: &SaveState) -> Result<(), SaveStateError> {
+        let expected = rom_hash(&self.rom);
+        if state.header.rom_hash != expected {
+            return Err(SaveStateError::RomMismatch {
+                expected,
+                found: state.header.rom_hash,
+            });
+        }
+        if state.header.platform != self.platform.name() {
+            return Err(SaveStateError::PlatformMismatch {
+                expected: self.platform.name().to_string(),
+                found: state.header.platform.clone(),
+            });
+        }
+        let snapshot = &state.snapshot;
+        if snapshot.ram.len() != self.memory.RAM.len()
+            || snapshot.display.planes.len() != self.display.state().planes.len()
+        {
+            return Err(SaveStateError::Corrupt(
+                "memory or display size of another platform",
+            ));
+        }
+        self.restore(snapshot);
+        self.history.clear();
+        self.rewind.clear();
         Ok(())
     }
-    fn fetch_and_execute(
-        cpu: &mut CPU,
-        memory: &mut Memory,
-        display: &mut Display,
-        keyboard: &mut Keypad,
-    ) -> Result<(), EmuError> {
-        /* Fetch and executes instruction from memory */
-        let instruction = cpu.fetch(memory);
-        cpu.decode_execute(memory, display, keyboard, instruction)?;
+    /* File of a numbered save slot, e.g. game.ch8.state1 next to game.ch8 */
+    pub fn state_slot_path(&self, slot: u8) -> Option<PathBuf> {
+        let rom = self.rom_path.as_ref()?;
+        let mut path = rom.clone().into_os_string();
+        path.push(format!(".state{slot}"));
+        Some(PathBuf::from(path))
+    }
+    pub fn save_state_slot(&self, slot: u8) -> Result<PathBuf, SaveStateError> {
+        let path = self.state_slot_path(slot).ok_or(SaveStateError::NoSlots)?;
+        self.save_state().save(&path)?;
+        Ok(path)
+    }
+    pub fn load_state_slot(&mut self, slot: u8) -> Result<PathBuf, SaveStateError> {
+        let path = self.state_slot_path(slot).ok_or(SaveStateError::NoSlots)?;
+        let state = SaveState::load(&path)?;
+        self.load_state(&state)?;
+        Ok(path)
+    }
+    #[inline]
+    pub fn screenshot_scale(&self) -> usize {
+        self.screenshot_scale
+    }
+    pub fn set_screenshot_scale(&mut self, scale: usize) {
+        self.screenshot_scale = scale.clamp(1, capture::frame::MAX_SCALE);
+    }
+    /* Saves the current frame as a PNG in `dir`, named after the ROM and the time */
+    pub fn screenshot<P: AsRef<Path>>(&self, dir: P) -> std::io::Result<PathBuf> {
+        save_screenshot(
+            &self.display,
+            self.screenshot_scale,
+            dir.as_ref(),
+            self.rom_path.as_deref(),
+        )
+    }
+    /* Starts recording the frames presented by run, finishing any capture already running */
+    pub fn start_capture(&mut self, recorder: Recorder) -> std::io::Result<()> {
+        if let Some(previous) = self.capture.replace(recorder) {
+            previous.finish()?;
+        }
         Ok(())
     }
+    /* Stops the running capture, the caller finishes it */
+    pub fn stop_capture(&mut self) -> Option<Recorder> {
+        self.capture.take()
+    }
+    #[inline]
+    pub fn is_capturing(&self) -> bool {
+        self.capture.is_some()
+    }
+    /* What the beeper plays in a frame, given whether the sound timer ran at its end */
+    pub fn tone(&self, sound_active: bool) -> Tone {
+        match (sound_active, self.cpu.AUDIO_PATTERN) {
+            (false, _) => Tone::Silent,
+            (true, Some(pattern)) => Tone::Pattern(pattern, self.cpu.PITCH),
+            (true, None) => Tone::Beep,
+        }
+    }
+    /* Adds the current display and `tone` to the running capture, if any */
+    pub fn capture_frame(&mut self, tone: Tone) -> std::io::Result<()> {
+        match &mut self.capture {
+            Some(recorder) => recorder.frame(&self.display, tone),
+            None => Ok(()),
+        }
+    }
+    /* Stops and finishes the running capture, if any, reporting the outcome on stderr */
+    pub fn finish_capture(&mut self) {
+        let Some(recorder) = self.capture.take() else {
+            return;
+        };
+        let path = recorder.video_path().map(|path| path.display().to_string());
+        match (recorder.finish(), path) {
+            (Ok(frames), Some(path)) => eprintln!("Captured {frames} frames to {path}"),
+            (Ok(frames), None) => eprintln!("Captured {frames} frames of audio"),
+            (Err(error), _) => eprintln!("Finishing capture failed: {error}"),
+        }
+    }
+    /* Starts a GIF clip in `dir` named after the ROM and the time, or finishes the running capture */
+    fn toggle_capture(&mut self, dir: &Path) -> String {
+        if let Some(recorder) = self.capture.take() {
+            let path = recorder.video_path().map(Path::to_path_buf);
+            return match (recorder.finish(), path) {
+                (Ok(frames), Some(path)) => format!("saved {} ({frames} frames)", path.display()),
+                (Ok(frames), None) => format!("capture stopped ({frames} frames)"),
+                (Err(error), _) => {
+                    eprintln!("Finishing capture failed: {error}");
+                    "capture failed".to_string()
+                }
+            };
+        }
+        let path = capture_path(
+            dir,
+            self.rom_path.as_deref(),
+            std::time::SystemTime::now(),
+            "gif",
+        );
+        match Recorder::start(Some(&path), None, self.screenshot_scale) {
+            Ok(recorder) => {
+                self.capture = Some(recorder);
+                "capturing".to_string()
+            }
+            Err(error) => {
+                eprintln!("Starting capture failed: {error}");
+                "capture failed".to_string()
+            }
+        }
+    }
+    /* Updates sound and delay timers for one frame, at timer_hz, one tick per frame at the default 60Hz */
+    pub fn tick_timers(&mut self) {
+        self.timer_accumulator += self.timer_hz;
+        while self.timer_accumulator >= FRAME_RATE {
+            self.timer_accumulator -= FRAME_RATE;
+            self.cpu.update_timers();
+        }
+    }
+    /* Window title showing the current speed */
+    fn title(&self) -> String {
+        format!(
+            "CHIP-8 | {} | {} instructions/frame",
+            self.platform, self.cycles_per_frame
+        )
+    }
+
+    #[inline]
+    /* Returns if the program executed 00FD (exit) */
+    pub fn has_exited(&self) -> bool {
+        self.cpu.exited
+    }
 
-    pub fn run(&mut self) -> Result<(), EmuError> {
-        const CPU_CYCLES_PER_FRAME: usize = 12;
-        while self.display.window_is_open() {
-            /* Limit FPS to 60Hz */
-            self.display.set_target_fps();
+    /* Captures registers, stack, disassembly and recent instructions for an error */
+    pub fn crash_report(&self, error: &EmuError) -> CrashReport {
+        CrashReport::new(error, &self.cpu, &self.memory, &self.history)
+    }
+
+    /* Keeps the last frame on screen, silent, until the frontend asks to quit */
+    pub fn hold_frame<F: Frontend>(&self, frontend: &mut F) {
+        frontend.stop_tone();
+        frontend.set_title(&format!("CHIP-8 | {} | halted", self.platform));
+        while !frontend.should_quit() {
+            frontend.poll_input(&mut Keypad::new());
+            frontend.present(&self.display);
+        }
+    }
+
+    /* Runs until the frontend asks to quit or the program exits */
+    pub fn run<F: Frontend>(&mut self, frontend: &mut F) -> Result<(), EmuError> {
+        let mut tone_on = false;
+        let mut audio_pattern = None;
+        frontend.set_title(&self.title());
+        while !frontend.should_quit() && !self.cpu.exited {
             /* Poll keyboard input */
-            self.keypad.update(&self.display);
-            /* CPU cycles */
-            for _ in 0..CPU_CYCLES_PER_FRAME {
-                CHIP8::fetch_and_execute(
-                    &mut self.cpu,
-                    &mut self.memory,
-                    &mut self.display,
-                    &mut self.keypad,
-                )?;
+            frontend.poll_input(&mut self.keypad);
+            let mut rewinding = false;
+            for hotkey in frontend.poll_hotkeys() {
+                match hotkey {
+                    Hotkey::SpeedUp => self.set_cycles_per_frame(self.cycles_per_frame + 1),
+                    Hotkey::SpeedDown => {
+                        self.set_cycles_per_frame(self.cycles_per_frame.saturating_sub(1))
+                    }
+                    Hotkey::Rewind => {
+                        rewinding = true;
+                        continue;
+                    }
+                    /* State hotkeys report their outcome in the title until the next hotkey */
+                    Hotkey::SaveState(slot) => {
+                        let status = match self.save_state_slot(slot) {
+                            Ok(_) => format!("saved slot {slot}"),
+                            Err(error) => {
+                                eprintln!("Saving slot {slot} failed: {error}");
+                                format!("saving slot {slot} failed")
+                            }
+                        };
+                        frontend.set_title(&format!("{} | {status}", self.title()));
+                        continue;
+                    }
+                    Hotkey::Screenshot => {
+                        let status = match self.screenshot(".") {
+                            Ok(path) => format!("saved {}", path.display()),
+                            Err(error) => {
+                                eprintln!("Saving screenshot failed: {error}");
+                                "screenshot failed".to_string()
+                            }
+                        };
+                        frontend.set_title(&format!("{} | {status}", self.title()));
+                        continue;
+                    }
+                    Hotkey::Record => {
+                        let status = self.toggle_capture(Path::new("."));
+                        frontend.set_title(&format!("{} | {status}", self.title()));
+                        continue;
+                    }
+                    Hotkey::LoadState(slot) => {
+                        let status = match self.load_state_slot(slot) {
+                            Ok(_) => format!("loaded slot {slot}"),
+                            Err(error) => {
+                                eprintln!("Loading slot {slot} failed: {error}");
+                                format!("loading slot {slot} failed")
+                            }
+                        };
+                        frontend.set_title(&format!("{} | {status}", self.title()));
+                        continue;
+                    }
+                }
+                frontend.set_title(&self.title());
+            }
+            /* Rewinding replaces running the frame, one recorded frame back per frame */
+            let frame = if rewinding {
+                self.rewind();
+                RunResult {
+                    sound_active: false,
+                    screen_changed: self.display.take_changed(),
+                    ..Default::default()
+                }
+            } else {
+                let frame = match self.run_frame() {
+                    Ok(frame) => frame,
+                    Err(error) => {
+                        /* Keep the frames captured up to the error */
+                        self.finish_capture();
+                        frontend.stop_tone();
+                        return Err(error);
+                    }
+                };
+                self.record_frame();
+                frame
+            };
+            /* Forward XO-CHIP audio pattern and pitch changes, before (re)starting the tone */
+            if let Some(pattern) = self.cpu.AUDIO_PATTERN
+                && audio_pattern != Some((pattern, self.cpu.PITCH))
+            {
+                audio_pattern = Some((pattern, self.cpu.PITCH));
+                frontend.set_audio_pattern(&pattern, self.cpu.PITCH);
+                if tone_on {
+                    frontend.start_tone();
+                }
+            }
+            /* Start or stop the beeper when the sound timer changes state */
+            if frame.sound_active != tone_on {
+                tone_on = frame.sound_active;
+                if tone_on {
+                    frontend.start_tone();
+                } else {
+                    frontend.stop_tone();
+                }
             }
-            /* Update sound and delay timers at 60Hz */
-            self.cpu.update_timers(&mut self.audio);
             /* Draw and update to window */
-            self.display.update();
+            frontend.present(&self.display);
+            if let Err(error) = self.capture_frame(self.tone(tone_on)) {
+                eprintln!("Capture stopped: {error}");
+                self.capture = None;
+            }
         }
+        frontend.stop_tone();
         Ok(())
     }
 }
@@ -0,0 +1,199 @@
+use crate::emulator::Display;
+
+/* Columns of blank pixels separating words, letters are one column apart */
+const WORD_GAP: usize = 3;
+/* Longest run of pixels in one column of a check mark, whose arms are diagonal */
+const MAX_CHECK_RUN: usize = 2;
+
+/* Result a test ROM draws next to a test name */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /* A check mark */
+    Pass,
+    /* A cross */
+    Fail,
+}
+impl Verdict {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Verdict::Pass => "pass",
+            Verdict::Fail => "fail",
+        }
+    }
+}
+
+/* Check mark or cross found on screen, in CHIP-8 pixels of the current mode */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Marker {
+    /* Text line, counted from 1 at the top */
+    pub line: usize,
+    /* Label the marker follows on its line, counted from 1, 0 if it starts the line */
+    pub label: usize,
+    /* Position among the markers following the same label, counted from 1 */
+    pub index: usize,
+    pub x: usize,
+    pub y: usize,
+    pub verdict: Verdict,
+}
+
+/* Screen area holding one glyph, with its lit pixels row by row */
+struct Glyph {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+    pixels: Vec<bool>,
+}
+impl Glyph {
+    #[inline]
+    fn lit(&self, x: usize, y: usize) -> bool {
+        self.pixels[y * self.width + x]
+    }
+    fn is_mirrored(&self) -> bool {
+        (0..self.height).all(|y| {
+            (0..self.width).all(|x| {
+                self.lit(x, y) == self.lit(self.width - 1 - x, y)
+                    && self.lit(x, y) == self.lit(x, self.height - 1 - y)
+            })
+        })
+    }
+    /* Two diagonals crossing: symmetric both ways, corners and center lit, edge middles blank */
+    fn is_cross(&self) -> bool {
+        let (w, h) = (self.width, self.height);
+        w >= 3
+            && h >= 3
+            && self.is_mirrored()
+            && self.lit(0, 0)
+            && self.lit(w / 2, h / 2)
+            && !self.lit(w / 2, 0)
+            && !self.lit(0, h / 2)
+    }
+    /*
+        A short diagonal down to a vertex, then a longer one up to the top right:
+        every column holds a short run of pixels, lowest at the vertex
+    */
+    fn is_check(&self) -> bool {
+        let (w, h) = (self.width, self.height);
+        if w < 3 || h < 3 {
+            return false;
+        }
+        let mut runs = Vec::with_capacity(w);
+        for x in 0..w {
+            let lit: Vec<usize> = (0..h).filter(|&y| self.lit(x, y)).collect();
+            let (Some(&top), Some(&bottom)) = (lit.first(), lit.last()) else {
+                return false;
+            };
+            if bottom - top + 1 != lit.len() || lit.len() > MAX_CHECK_RUN {
+                return false;
+            }
+            runs.push((top, bottom));
+        }
+        let vertex = (0..w).max_by_key(|&x| (runs[x].1, w - x)).unwrap_or(0);
+        vertex >= 1
+            && runs[vertex].1 == h - 1
+            && runs[0].0 > 0
+            && runs[w - 1].0 == 0
+            && runs[..=vertex]
+                .windows(2)
+                .all(|pair| pair[0].1 <= pair[1].1)
+            && runs[vertex..].windows(2).all(|pair| pair[0].1 >= pair[1].1)
+    }
+    fn verdict(&self) -> Option<Verdict> {
+        if self.is_check() {
+            Some(Verdict::Pass)
+        } else if self.is_cross() {
+            Some(Verdict::Fail)
+        } else {
+            None
+        }
+    }
+}
+
+/*
+    Finds the check marks and crosses test ROMs draw after test names, reading the
+    screen as lines of text. Only markers ending a word count, so an X inside a
+    name like 8XY4 is not mistaken for a failure
+*/
+pub fn find_markers(display: &Display) -> Vec<Marker> {
+    let (width, height) = (display.width(), display.height());
+    let scale = display.buffer_width() / width;
+    let lit = |x: usize, y: usize| {
+        display.get_planes(y * scale * display.buffer_width() + x * scale) != 0
+    };
+    let row_lit = |y: usize| (0..width).any(|x| lit(x, y));
+
+    let mut markers = Vec::new();
+    let mut line = 0;
+    let mut y = 0;
+    while y < height {
+        if !row_lit(y) {
+            y += 1;
+            continue;
+        }
+        let top = y;
+        while y < height && row_lit(y) {
+            y += 1;
+        }
+        line += 1;
+        let glyphs = glyphs(top, y, width, &lit);
+        let (mut label, mut index) = (0, 0);
+        for word in glyphs.chunk_by(|a, b| b.x - (a.x + a.width) < WORD_GAP) {
+            let verdicts: Vec<Option<Verdict>> = word.iter().map(Glyph::verdict).collect();
+            let trailing = verdicts.iter().rev().take_while(|v| v.is_some()).count();
+            if trailing < word.len() {
+                label += 1;
+                index = 0;
+            }
+            let ends = word.iter().zip(verdicts).skip(word.len() - trailing);
+            for (glyph, verdict) in ends {
+                index += 1;
+                markers.extend(verdict.map(|verdict| Marker {
+                    line,
+                    label,
+                    index,
+                    x: glyph.x,
+                    y: glyph.y,
+                    verdict,
+                }));
+            }
+        }
+    }
+    markers
+}
+
+/* Splits the rows top..bottom into glyphs at blank columns, each cropped to its lit pixels */
+fn glyphs<L: Fn(usize, usize) -> bool>(
+    top: usize,
+    bottom: usize,
+    width: usize,
+    lit: &L,
+) -> Vec<Glyph> {
+    let column_lit = |x: usize| (top..bottom).any(|y| lit(x, y));
+    let mut glyphs = Vec::new();
+    let mut x = 0;
+    while x < width {
+        if !column_lit(x) {
+            x += 1;
+            continue;
+        }
+        let left = x;
+        while x < width && column_lit(x) {
+            x += 1;
+        }
+        let rows: Vec<usize> = (top..bottom)
+            .filter(|&y| (left..x).any(|x| lit(x, y)))
+            .collect();
+        let (first, last) = (rows[0], rows[rows.len() - 1]);
+        glyphs.push(Glyph {
+            x: left,
+            y: first,
+            width: x - left,
+            height: last - first + 1,
+            pixels: (first..=last)
+                .flat_map(|y| (left..x).map(move |x| (x, y)))
+                .map(|(x, y)| lit(x, y))
+                .collect(),
+        });
+    }
+    glyphs
+}
@@ -0,0 +1,187 @@
+pub mod markers;
+pub mod report;
+pub mod script;
+pub mod suite;
+
+pub use markers::{Marker, Verdict, find_markers};
+pub use report::RunReport;
+pub use script::{KeyPress, KeyScript};
+pub use suite::{SuiteReport, SuiteTest, TestResult};
+
+use crate::CHIP8;
+use crate::emulator::StepResult;
+use crate::emulator::errors::EmuError;
+use crate::frontend::Movie;
+
+/* Opcode of JUMP NNN, a jump to its own address loops forever */
+const JUMP: u16 = 0x1000;
+
+/* Events that end a headless run before its frame limit */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopCondition {
+    /* PC reaches an address, checked after every instruction */
+    Pc(u16),
+    /* A presented frame has this Display::frame_hash */
+    FrameHash(u64),
+    /* A JUMP to its own address, how most programs idle once they are done */
+    SelfLoop,
+}
+
+/* Why a headless run ended */
+#[derive(Debug)]
+pub enum Outcome {
+    /* Ran every frame without meeting a stop condition */
+    FrameLimit,
+    /* Reached the address of a StopCondition::Pc */
+    Pc(u16),
+    /* Presented a frame with a hash of a StopCondition::FrameHash */
+    FrameHash(u64),
+    /* Started looping on a JUMP to this address */
+    SelfLoop(u16),
+    /* The program executed 00FD */
+    Exited,
+    /* Emulation halted on an error */
+    Fault(EmuError),
+}
+impl Outcome {
+    /*
+        Whether a stop condition was met or the program exited, running every
+        frame only succeeds when there were no stop conditions to meet
+    */
+    pub fn is_success(&self, has_conditions: bool) -> bool {
+        match self {
+            Outcome::FrameLimit => !has_conditions,
+            Outcome::Fault(_) => false,
+            _ => true,
+        }
+    }
+}
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::FrameLimit => write!(f, "frame limit reached"),
+            Outcome::Pc(addr) => write!(f, "reached PC {addr:#06x}"),
+            Outcome::FrameHash(hash) => write!(f, "frame hash {hash:016x} appeared"),
+            Outcome::SelfLoop(addr) => write!(f, "infinite loop at {addr:#06x}"),
+            Outcome::Exited => write!(f, "program exited"),
+            Outcome::Fault(error) => write!(f, "halted: {error}"),
+        }
+    }
+}
+
+/* Runs a ROM without a frontend, for a number of frames or until a stop condition */
+#[derive(Debug)]
+pub struct Runner {
+    chip8: CHIP8,
+    max_frames: usize,
+    conditions: Vec<StopCondition>,
+    script: KeyScript,
+    /* Recorded keypad states, replacing the script */
+    movie: Option<Movie>,
+    /* Frames run so far, including one stopped early */
+    frame: usize,
+    /* Whether the sound timer was running at the end of each frame */
+    sound: Vec<bool>,
+}
+impl Runner {
+    pub fn new(chip8: CHIP8, max_frames: usize) -> Runner {
+        Runner {
+            chip8,
+            max_frames,
+            conditions: Vec::new(),
+            script: KeyScript::new(),
+            movie: None,
+            frame: 0,
+            sound: Vec::new(),
+        }
+    }
+    pub fn add_condition(&mut self, condition: StopCondition) {
+        self.conditions.push(condition);
+    }
+    /* Replaces the keypad input with scripted presses */
+    pub fn set_script(&mut self, script: KeyScript) {
+        self.script = script;
+    }
+    /* Replaces the keypad input with a movie's key masks, for a machine it prepared */
+    pub fn set_movie(&mut self, movie: Movie) {
+        self.movie = Some(movie);
+    }
+    #[inline]
+    pub fn chip8(&self) -> &CHIP8 {
+        &self.chip8
+    }
+    #[inline]
+    pub fn chip8_mut(&mut self) -> &mut CHIP8 {
+        &mut self.chip8
+    }
+    #[inline]
+    pub fn into_inner(self) -> CHIP8 {
+        self.chip8
+    }
+    #[inline]
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+    /* Frames that ended with the sound timer running */
+    pub fn sound_frames(&self) -> usize {
+        self.sound.iter().filter(|&&sound| sound).count()
+    }
+    #[inline]
+    pub fn sound(&self) -> &[bool] {
+        &self.sound
+    }
+    /* Runs until the frame limit, a stop condition, 00FD or an error */
+    pub fn run(&mut self) -> Outcome {
+        while self.frame < self.max_frames {
+            match &self.movie {
+                Some(movie) => movie.apply(self.frame, self.chip8.keypad_mut()),
+                None => self.script.apply(self.frame, self.chip8.keypad_mut()),
+            }
+            let mut hit = None;
+            let conditions = &self.conditions;
+            let result = self.chip8.run_frame_until(|step| {
+                hit = step_condition(conditions, step);
+                hit.is_some()
+            });
+            self.frame += 1;
+            let result = match result {
+                Ok((result, _)) => result,
+                Err(error) => return Outcome::Fault(error),
+            };
+            self.sound.push(result.sound_active);
+            let tone = self.chip8.tone(result.sound_active);
+            if let Err(error) = self.chip8.capture_frame(tone) {
+                return Outcome::Fault(error.into());
+            }
+            if let Some(outcome) = hit {
+                return outcome;
+            }
+            if self.chip8.has_exited() {
+                return Outcome::Exited;
+            }
+            let hash = self.chip8.display().frame_hash();
+            if self.conditions.contains(&StopCondition::FrameHash(hash)) {
+                return Outcome::FrameHash(hash);
+            }
+        }
+        Outcome::FrameLimit
+    }
+    /* Registers, stack and screen after the run */
+    pub fn report(&self, outcome: &Outcome) -> RunReport {
+        let success = outcome.is_success(!self.conditions.is_empty());
+        RunReport::new(&self.chip8, outcome, success, self.frame)
+    }
+}
+
+/* Condition met by an instruction, PC and JUMP conditions are checked between instructions */
+fn step_condition(conditions: &[StopCondition], step: &StepResult) -> Option<Outcome> {
+    conditions.iter().find_map(|condition| match *condition {
+        StopCondition::Pc(addr) if step.pc_after == addr => Some(Outcome::Pc(addr)),
+        StopCondition::SelfLoop
+            if step.opcode & 0xF000 == JUMP && step.opcode & 0x0FFF == step.pc_before =>
+        {
+            Some(Outcome::SelfLoop(step.pc_before))
+        }
+        _ => None,
+    })
+}
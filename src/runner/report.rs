@@ -0,0 +1,107 @@
+use crate::CHIP8;
+use crate::capture::Frame;
+use crate::runner::Outcome;
+use serde_json::{Value, json};
+use std::fmt;
+
+/* Machine state at the end of a headless run, printed as text or JSON for CI */
+#[allow(non_snake_case)]
+#[derive(Debug, Clone)]
+pub struct RunReport {
+    pub outcome: String,
+    pub success: bool,
+    pub frames: usize,
+    pub cycles: u64,
+    pub V: [u8; 16],
+    pub I: u16,
+    pub PROGRAM_COUNTER: u16,
+    pub STACK_POINTER: u8,
+    pub D_TIMER: u8,
+    pub S_TIMER: u8,
+    pub stack: Vec<u16>,
+    /* Display::frame_hash of the final screen */
+    pub frame_hash: u64,
+    pub screen: Frame,
+}
+impl RunReport {
+    pub fn new(chip8: &CHIP8, outcome: &Outcome, success: bool, frames: usize) -> RunReport {
+        let cpu = chip8.cpu();
+        RunReport {
+            outcome: outcome.to_string(),
+            success,
+            frames,
+            cycles: chip8.cycles(),
+            V: cpu.V,
+            I: cpu.I,
+            PROGRAM_COUNTER: cpu.PROGRAM_COUNTER,
+            STACK_POINTER: cpu.STACK_POINTER,
+            D_TIMER: cpu.D_TIMER,
+            S_TIMER: cpu.S_TIMER,
+            stack: chip8.memory().STACK.clone(),
+            frame_hash: chip8.display().frame_hash(),
+            screen: Frame::capture(chip8.display()),
+        }
+    }
+    /* Same fields as the text report, the screen as one string per row */
+    pub fn to_json(&self) -> Value {
+        json!({
+            "outcome": self.outcome,
+            "success": self.success,
+            "frames": self.frames,
+            "cycles": self.cycles,
+            "registers": {
+                "V": self.V,
+                "I": self.I,
+                "PC": self.PROGRAM_COUNTER,
+                "SP": self.STACK_POINTER,
+                "DT": self.D_TIMER,
+                "ST": self.S_TIMER,
+            },
+            "stack": self.stack,
+            "frame_hash": format!("{:016x}", self.frame_hash),
+            "screen": self.screen.to_ascii().lines().collect::<Vec<_>>(),
+        })
+    }
+}
+
+impl fmt::Display for RunReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "=== CHIP-8 run report ===")?;
+        writeln!(f, "Outcome: {}", self.outcome)?;
+        writeln!(
+            f,
+            "Ran {} frames, {} instructions, final frame hash {:016x}",
+            self.frames, self.cycles, self.frame_hash
+        )?;
+
+        writeln!(f, "\nRegisters:")?;
+        for (i, regs) in self.V.chunks(8).enumerate() {
+            let line: Vec<String> = regs
+                .iter()
+                .enumerate()
+                .map(|(j, v)| format!("V{:X}={:02X}", i * 8 + j, v))
+                .collect();
+            writeln!(f, "  {}", line.join(" "))?;
+        }
+        writeln!(
+            f,
+            "  I={:04X} PC={:04X} SP={:02X} DT={:02X} ST={:02X}",
+            self.I, self.PROGRAM_COUNTER, self.STACK_POINTER, self.D_TIMER, self.S_TIMER
+        )?;
+
+        writeln!(f, "\nStack ({} entries, top first):", self.stack.len())?;
+        if self.stack.is_empty() {
+            writeln!(f, "  <empty>")?;
+        }
+        for (depth, addr) in self.stack.iter().rev().enumerate() {
+            writeln!(f, "  #{depth:<2} {addr:#06x}")?;
+        }
+
+        writeln!(
+            f,
+            "\nScreen ({}x{}):",
+            self.screen.width, self.screen.height
+        )?;
+        write!(f, "{}", self.screen.to_ascii())
+    }
+}
@@ -0,0 +1,104 @@
+use crate::emulator::Keypad;
+use crate::emulator::errors::KeyScriptError;
+use crate::emulator::keyboard::NUM_KEYS;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/* Frames a key stays down when a press gives no duration, enough for ROMs polling every few frames */
+pub const DEFAULT_PRESS_FRAMES: usize = 6;
+
+/* Key held down for `frames` frames from frame `start`, written KEY@FRAME[:FRAMES], e.g. 5@60:10 */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyPress {
+    pub key: u8,
+    pub start: usize,
+    pub frames: usize,
+}
+impl KeyPress {
+    #[inline]
+    pub fn is_down(&self, frame: usize) -> bool {
+        frame >= self.start && frame - self.start < self.frames
+    }
+}
+impl fmt::Display for KeyPress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:X}@{}:{}", self.key, self.start, self.frames)
+    }
+}
+impl FromStr for KeyPress {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, when) = s
+            .split_once('@')
+            .ok_or_else(|| format!("expected KEY@FRAME[:FRAMES], got '{s}'"))?;
+        let key = u8::from_str_radix(key, 16)
+            .ok()
+            .filter(|&key| (key as usize) < NUM_KEYS)
+            .ok_or_else(|| format!("invalid key '{key}', expected 0-F"))?;
+        let (start, frames) = match when.split_once(':') {
+            Some((start, frames)) => (start, frames.parse().ok().filter(|&n| n > 0)),
+            None => (when, Some(DEFAULT_PRESS_FRAMES)),
+        };
+        let start = start
+            .parse()
+            .map_err(|_| format!("invalid frame number '{start}'"))?;
+        let frames = frames.ok_or_else(|| format!("invalid press duration in '{s}'"))?;
+        Ok(KeyPress { key, start, frames })
+    }
+}
+
+/* Scripted key presses fed to the keypad frame by frame, replacing live input */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyScript {
+    presses: Vec<KeyPress>,
+}
+impl KeyScript {
+    pub fn new() -> KeyScript {
+        KeyScript {
+            ..Default::default()
+        }
+    }
+    pub fn push(&mut self, press: KeyPress) {
+        self.presses.push(press);
+    }
+    #[inline]
+    pub fn presses(&self) -> &[KeyPress] {
+        &self.presses
+    }
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.presses.is_empty()
+    }
+    /* Sets every key to whether a press holds it down during `frame` */
+    pub fn apply(&self, frame: usize, keypad: &mut Keypad) {
+        for key in 0..NUM_KEYS {
+            let down = self
+                .presses
+                .iter()
+                .any(|press| press.key as usize == key && press.is_down(frame));
+            keypad.set_key(key, down);
+        }
+    }
+    /* Reads a script of whitespace separated presses, '#' starts a comment */
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<KeyScript, KeyScriptError> {
+        std::fs::read_to_string(path)?.parse()
+    }
+}
+impl FromStr for KeyScript {
+    type Err = KeyScriptError;
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut script = KeyScript::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default();
+            for word in line.split_whitespace() {
+                let press = word.parse().map_err(|message| KeyScriptError::Syntax {
+                    line: index + 1,
+                    message,
+                })?;
+                script.push(press);
+            }
+        }
+        Ok(script)
+    }
+}
@@ -0,0 +1,247 @@
+use crate::CHIP8;
+use crate::emulator::errors::EmuError;
+use crate::emulator::{Platform, Rng};
+use crate::runner::{KeyPress, KeyScript, Outcome, Runner, StopCondition, Verdict, find_markers};
+use serde_json::{Value, json};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/* RAM byte the suite's menus read at startup, a non-zero value picks that menu item */
+pub const MENU_CHOICE_ADDR: usize = 0x1FF;
+/* Frames the ROM gets to draw its screen before scripted key presses */
+const SETTLE_FRAMES: usize = 60;
+/* Key the beep test sounds the buzzer for, and how long it is held */
+const BEEP_KEY: u8 = 0xB;
+const BEEP_PRESS_FRAMES: usize = 30;
+/* The ROM keeps restarting a short sound timer while the key is down, it runs out after release */
+const BEEP_TAIL_FRAMES: usize = 10;
+/* Key pressed and released for the FX0A test */
+const GETKEY_KEY: u8 = 0x5;
+const SEED: u64 = 0;
+
+/*
+    ROMs of Timendus' CHIP-8 test suite that report their results on screen
+    https://github.com/Timendus/chip8-test-suite
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuiteTest {
+    /* Opcode results, corax89's test extended */
+    Corax,
+    /* VF results of the 8XY_ opcodes */
+    Flags,
+    /* Quirks of the platform picked in the menu */
+    Quirks,
+    /* FX0A, waiting for a key press and release */
+    Keypad,
+    /* Sound while a key is held */
+    Beep,
+}
+impl SuiteTest {
+    pub const ALL: [SuiteTest; 5] = [
+        SuiteTest::Corax,
+        SuiteTest::Flags,
+        SuiteTest::Quirks,
+        SuiteTest::Keypad,
+        SuiteTest::Beep,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SuiteTest::Corax => "corax+",
+            SuiteTest::Flags => "flags",
+            SuiteTest::Quirks => "quirks",
+            SuiteTest::Keypad => "keypad",
+            SuiteTest::Beep => "beep",
+        }
+    }
+    /* Test a ROM file holds, from its name, e.g. 3-corax+.ch8 */
+    pub fn identify(path: &Path) -> Option<SuiteTest> {
+        let name = path.file_stem()?.to_str()?.to_ascii_lowercase();
+        SuiteTest::ALL
+            .into_iter()
+            .find(|test| name.contains(test.name().trim_end_matches('+')))
+    }
+    /* Menu item stored at MENU_CHOICE_ADDR, so the test starts without input */
+    pub fn menu_choice(&self, platform: Platform) -> Option<u8> {
+        match self {
+            SuiteTest::Quirks => Some(match platform {
+                Platform::Chip8 | Platform::CosmacVip => 1,
+                Platform::XoChip => 3,
+                /* The HP48 interpreters, SUPER-CHIP "legacy" in the menu */
+                Platform::Chip48 | Platform::SuperChip10 | Platform::SuperChip11 => 4,
+            }),
+            /* Ex9E and ExA1 only light up keys, FX0A reports a result */
+            SuiteTest::Keypad => Some(3),
+            _ => None,
+        }
+    }
+    fn max_frames(&self) -> usize {
+        match self {
+            /* The display wait quirk is measured over many frames */
+            SuiteTest::Quirks => 1800,
+            _ => 600,
+        }
+    }
+    fn script(&self) -> KeyScript {
+        let press = |key, frames| KeyPress {
+            key,
+            start: SETTLE_FRAMES,
+            frames,
+        };
+        let mut script = KeyScript::new();
+        match self {
+            SuiteTest::Keypad => script.push(press(GETKEY_KEY, 10)),
+            SuiteTest::Beep => script.push(press(BEEP_KEY, BEEP_PRESS_FRAMES)),
+            _ => {}
+        }
+        script
+    }
+    /* Runs the ROM at `rom` on `platform`, `menu` overrides the menu item picked */
+    pub fn run(
+        &self,
+        rom: &Path,
+        platform: Platform,
+        menu: Option<u8>,
+    ) -> Result<SuiteReport, EmuError> {
+        let mut chip8 = CHIP8::with_platform(platform);
+        chip8.set_rng(Rng::from_seed(SEED));
+        chip8.set_rewind_frames(0);
+        chip8.load(rom)?;
+        if let Some(choice) = menu.or(self.menu_choice(platform)) {
+            chip8.memory_mut().write(MENU_CHOICE_ADDR, choice)?;
+        }
+        let mut runner = Runner::new(chip8, self.max_frames());
+        runner.set_script(self.script());
+        /* The tests idle in a JUMP to itself once the results are drawn */
+        if *self != SuiteTest::Beep {
+            runner.add_condition(StopCondition::SelfLoop);
+        }
+        let outcome = runner.run();
+        let results = match self {
+            SuiteTest::Beep => vec![TestResult {
+                name: format!("sound while key {BEEP_KEY:X} is held"),
+                verdict: beep_verdict(runner.sound()),
+                position: None,
+            }],
+            _ => find_markers(runner.chip8().display())
+                .into_iter()
+                .map(|marker| TestResult {
+                    name: format!(
+                        "line {} label {} result {}",
+                        marker.line, marker.label, marker.index
+                    ),
+                    verdict: marker.verdict,
+                    position: Some((marker.x, marker.y)),
+                })
+                .collect(),
+        };
+        Ok(SuiteReport {
+            test: *self,
+            rom: rom.to_path_buf(),
+            platform,
+            error: match &outcome {
+                Outcome::Fault(error) => Some(error.to_string()),
+                _ => None,
+            },
+            frames: runner.frame(),
+            results,
+        })
+    }
+}
+/* Sound on every frame the beep key is held, silence before the press and once the timer ran out */
+fn beep_verdict(sound: &[bool]) -> Verdict {
+    let release = SETTLE_FRAMES + BEEP_PRESS_FRAMES;
+    let held = sound.get(SETTLE_FRAMES..release).unwrap_or_default();
+    let before = &sound[..SETTLE_FRAMES.min(sound.len())];
+    let after = sound.get(release + BEEP_TAIL_FRAMES..).unwrap_or_default();
+    let beeps = held.len() == BEEP_PRESS_FRAMES && held.iter().all(|&sound| sound);
+    match beeps && !before.iter().chain(after).any(|&sound| sound) {
+        true => Verdict::Pass,
+        false => Verdict::Fail,
+    }
+}
+
+impl fmt::Display for SuiteTest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/* One result read from the screen */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestResult {
+    /* Where the result was drawn, the suite's labels are not read */
+    pub name: String,
+    pub verdict: Verdict,
+    /* Marker position in CHIP-8 pixels */
+    pub position: Option<(usize, usize)>,
+}
+
+/* Results of one test ROM */
+#[derive(Debug, Clone)]
+pub struct SuiteReport {
+    pub test: SuiteTest,
+    pub rom: PathBuf,
+    pub platform: Platform,
+    /* Error emulation halted on */
+    pub error: Option<String>,
+    pub frames: usize,
+    pub results: Vec<TestResult>,
+}
+impl SuiteReport {
+    pub fn failures(&self) -> impl Iterator<Item = &TestResult> {
+        self.results
+            .iter()
+            .filter(|result| result.verdict == Verdict::Fail)
+    }
+    /* Whether the ROM ran without errors and drew results to read */
+    pub fn is_conclusive(&self) -> bool {
+        self.error.is_none() && !self.results.is_empty()
+    }
+    pub fn passed(&self) -> bool {
+        self.is_conclusive() && self.failures().next().is_none()
+    }
+    pub fn to_json(&self) -> Value {
+        json!({
+            "test": self.test.name(),
+            "rom": self.rom.display().to_string(),
+            "platform": self.platform.name(),
+            "passed": self.passed(),
+            "conclusive": self.is_conclusive(),
+            "error": self.error,
+            "frames": self.frames,
+            "results": self.results.iter().map(|result| json!({
+                "name": result.name,
+                "result": result.verdict.name(),
+                "x": result.position.map(|(x, _)| x),
+                "y": result.position.map(|(_, y)| y),
+            })).collect::<Vec<_>>(),
+        })
+    }
+}
+impl fmt::Display for SuiteReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let failed = self.failures().count();
+        write!(
+            f,
+            "{} ({}, {}): ",
+            self.test,
+            self.rom.display(),
+            self.platform
+        )?;
+        match &self.error {
+            Some(error) => writeln!(f, "halted: {error}")?,
+            None if self.results.is_empty() => {
+                writeln!(f, "no results found on screen after {} frames", self.frames)?
+            }
+            None => writeln!(f, "{} passed, {failed} failed", self.results.len() - failed)?,
+        }
+        for result in self.failures() {
+            match result.position {
+                Some((x, y)) => writeln!(f, "  FAIL {} at ({x}, {y})", result.name)?,
+                None => writeln!(f, "  FAIL {}", result.name)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+/*
+    Drives the Debug Adapter Protocol server with a scripted editor session:
+    Content-Length framed requests in, responses and events out.
+*/
+use chip8::debugger::DapServer;
+use chip8::frontend::NullFrontend;
+use serde_json::{Value, json};
+use std::fs;
+use std::io::Cursor;
+use std::path::PathBuf;
+
+/* 0x200: V0 = 5, 0x202: jump to itself */
+const PROGRAM: [u8; 4] = [0x60, 0x05, 0x12, 0x02];
+/* Line 3 of the source is the jump */
+const SYMBOLS: &str =
+    r#"{"source": "loop.8o", "lines": {"2": "0x200", "3": "0x202"}, "labels": {"main": 512}}"#;
+/* The server gives up after this many frames if the session does not end */
+const SERVER_FRAMES: usize = 600;
+
+/* Writes a file for launch into the temporary directory */
+fn temp_file(name: &str, contents: &[u8]) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("chip8-dap-{}-{name}", std::process::id()));
+    fs::write(&path, contents).expect("write temporary file");
+    path
+}
+
+fn frame(message: &Value) -> String {
+    let text = message.to_string();
+    format!("Content-Length: {}\r\n\r\n{text}", text.len())
+}
+
+/* Serves the requests in order and returns every message the server sent */
+fn session(requests: &[(&str, Value)]) -> Vec<Value> {
+    let input: String = requests
+        .iter()
+        .enumerate()
+        .map(|(seq, (command, arguments))| {
+            frame(&json!({
+                "seq": seq + 1,
+                "type": "request",
+                "command": command,
+                "arguments": arguments,
+            }))
+        })
+        .collect();
+    let mut output = Vec::new();
+    DapServer::new()
+        .serve(
+            Cursor::new(input.into_bytes()),
+            &mut output,
+            &mut NullFrontend::with_max_frames(SERVER_FRAMES),
+        )
+        .expect("serve session");
+
+    let mut messages = Vec::new();
+    let mut rest = output.as_slice();
+    while !rest.is_empty() {
+        let text = std::str::from_utf8(rest).expect("output is text");
+        let (header, body) = text.split_once("\r\n\r\n").expect("message header");
+        let length: usize = header
+            .strip_prefix("Content-Length: ")
+            .and_then(|length| length.parse().ok())
+            .expect("Content-Length header");
+        messages.push(serde_json::from_str(&body[..length]).expect("message is JSON"));
+        rest = &rest[header.len() + 4 + length..];
+    }
+    messages
+}
+
+fn response<'a>(messages: &'a [Value], command: &str) -> &'a Value {
+    messages
+        .iter()
+        .find(|m| m["type"] == "response" && m["command"] == command)
+        .unwrap_or_else(|| panic!("no response to {command}"))
+}
+
+fn events<'a>(messages: &'a [Value], event: &'a str) -> impl Iterator<Item = &'a Value> {
+    messages
+        .iter()
+        .filter(move |m| m["type"] == "event" && m["event"] == event)
+}
+
+#[test]
+fn scripted_session_stops_at_a_line_breakpoint() {
+    let program = temp_file("loop.ch8", &PROGRAM);
+    let symbols = temp_file("loop.json", SYMBOLS.as_bytes());
+    let messages = session(&[
+        ("initialize", json!({ "adapterID": "chip8" })),
+        (
+            "launch",
+            json!({ "program": program, "symbols": symbols, "platform": "chip8" }),
+        ),
+        (
+            "setBreakpoints",
+            json!({ "source": { "path": "loop.8o" }, "breakpoints": [{ "line": 3 }, { "line": 9 }] }),
+        ),
+        ("continue", json!({ "threadId": 1 })),
+        ("stackTrace", json!({ "threadId": 1 })),
+        ("disconnect", json!({})),
+    ]);
+    fs::remove_file(program).ok();
+    fs::remove_file(symbols).ok();
+
+    for m in messages.iter().filter(|m| m["type"] == "response") {
+        assert_eq!(m["success"], true, "{m}");
+    }
+    assert_eq!(
+        response(&messages, "initialize")["body"]["supportsInstructionBreakpoints"],
+        true
+    );
+    assert_eq!(events(&messages, "initialized").count(), 1);
+    let breakpoints = &response(&messages, "setBreakpoints")["body"]["breakpoints"];
+    assert_eq!(breakpoints[0]["verified"], true);
+    assert_eq!(breakpoints[0]["instructionReference"], "0x0202");
+    assert_eq!(breakpoints[1]["verified"], false, "no address for line 9");
+
+    let stopped: Vec<_> = events(&messages, "stopped").collect();
+    assert_eq!(stopped.len(), 1);
+    assert_eq!(stopped[0]["body"]["reason"], "breakpoint");
+    let frames = &response(&messages, "stackTrace")["body"]["stackFrames"];
+    assert_eq!(frames[0]["line"], 3, "stopped on the jump");
+    assert_eq!(events(&messages, "terminated").count(), 1);
+}
+
+#[test]
+fn breakpoint_requests_only_replace_their_own_breakpoints() {
+    let program = temp_file("replace.ch8", &PROGRAM);
+    let messages = session(&[
+        ("initialize", json!({})),
+        ("launch", json!({ "program": program })),
+        (
+            "setInstructionBreakpoints",
+            json!({ "breakpoints": [{ "instructionReference": "0x200", "offset": 2 }] }),
+        ),
+        ("setFunctionBreakpoints", json!({ "breakpoints": [] })),
+        ("continue", json!({ "threadId": 1 })),
+        ("disconnect", json!({})),
+    ]);
+    fs::remove_file(program).ok();
+
+    let breakpoints = &response(&messages, "setInstructionBreakpoints")["body"]["breakpoints"];
+    assert_eq!(breakpoints[0]["instructionReference"], "0x0202");
+    let stopped: Vec<_> = events(&messages, "stopped").collect();
+    assert_eq!(stopped.len(), 1, "clearing function breakpoints kept 0x202");
+    assert_eq!(stopped[0]["body"]["reason"], "breakpoint");
+}
@@ -0,0 +1,140 @@
+/*
+    Drives the GDB remote serial protocol server over a loopback connection,
+    like a scripted GDB session: packets, acks, checksums and stop replies.
+*/
+use chip8::CHIP8;
+use chip8::debugger::{Debugger, GdbServer};
+use chip8::frontend::NullFrontend;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/* 0x200: V0 = 5, 0x202: jump to itself */
+const PROGRAM: [u8; 4] = [0x60, 0x05, 0x12, 0x02];
+/* The server gives up after this many idle frames if the client stops talking */
+const SERVER_FRAMES: usize = 3000;
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+struct Client {
+    stream: TcpStream,
+    received: Vec<u8>,
+}
+impl Client {
+    fn send_raw(&mut self, bytes: &[u8]) {
+        self.stream.write_all(bytes).expect("send to server");
+    }
+    fn send(&mut self, packet: &str) {
+        let checksum = packet.bytes().fold(0u8, |sum, b| sum.wrapping_add(b));
+        self.send_raw(format!("${packet}#{checksum:02x}").as_bytes());
+    }
+    /* Next byte from the server */
+    fn byte(&mut self) -> u8 {
+        if self.received.is_empty() {
+            let mut chunk = [0; 1024];
+            let n = self.stream.read(&mut chunk).expect("reply from server");
+            assert!(n > 0, "server closed the connection");
+            self.received.extend_from_slice(&chunk[..n]);
+        }
+        self.received.remove(0)
+    }
+    /* Next packet, checking its checksum */
+    fn reply(&mut self) -> String {
+        while self.byte() != b'$' {}
+        let mut data = Vec::new();
+        loop {
+            match self.byte() {
+                b'#' => break,
+                b => data.push(b),
+            }
+        }
+        let checksum = [self.byte(), self.byte()];
+        let checksum = u8::from_str_radix(std::str::from_utf8(&checksum).unwrap(), 16).unwrap();
+        assert_eq!(
+            checksum,
+            data.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)),
+            "reply checksum"
+        );
+        String::from_utf8(data).expect("reply is text")
+    }
+    /* Sends a packet and returns the reply, after the server's ack */
+    fn request(&mut self, packet: &str) -> String {
+        self.send(packet);
+        assert_eq!(self.byte(), b'+', "ack of {packet}");
+        self.reply()
+    }
+}
+
+fn start_server() -> (Client, JoinHandle<CHIP8>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind loopback");
+    let addr = listener.local_addr().expect("local address");
+    let server = thread::spawn(move || {
+        let mut chip8 = CHIP8::new();
+        chip8.load_bytes(PROGRAM).expect("program fits");
+        let mut server = GdbServer::new(Debugger::new(chip8));
+        let (stream, _) = listener.accept().expect("accept client");
+        server
+            .serve(stream, &mut NullFrontend::with_max_frames(SERVER_FRAMES))
+            .expect("serve client");
+        server.into_inner().into_inner()
+    });
+    let stream = TcpStream::connect(addr).expect("connect to server");
+    stream.set_read_timeout(Some(READ_TIMEOUT)).unwrap();
+    let client = Client {
+        stream,
+        received: Vec::new(),
+    };
+    (client, server)
+}
+
+#[test]
+fn scripted_session() {
+    let (mut client, server) = start_server();
+    assert_eq!(client.request("?"), "S05");
+    /* V0-VF, I, PC (little endian), SP, DT and ST */
+    let registers = client.request("g");
+    assert_eq!(
+        registers,
+        format!("{}0000{}000000", "00".repeat(16), "0002")
+    );
+
+    assert_eq!(client.request("Z0,202,2"), "OK");
+    assert_eq!(client.request("c"), "S05");
+    assert_eq!(
+        client.request("p11"),
+        "0202",
+        "PC stopped at the breakpoint"
+    );
+    assert_eq!(client.request("p0"), "05");
+    assert_eq!(client.request("s"), "S05");
+    assert_eq!(client.request("bs"), "S05");
+    assert_eq!(client.request("p11"), "0202", "stepped back to the jump");
+
+    /* Stepping back replays from a snapshot, so edit the machine afterwards */
+    assert_eq!(client.request("M300,3:abcdef"), "OK");
+    assert_eq!(client.request("m300,3"), "abcdef");
+    assert_eq!(client.request("M300,2:ab"), "E01", "length mismatch");
+    assert_eq!(client.request("Mffffffffffffffff,1:00"), "E01");
+    assert_eq!(client.request("M1000,1:00"), "E01", "past the end of RAM");
+    assert_eq!(client.request("P0=2a"), "OK");
+    assert_eq!(client.request("D"), "OK");
+
+    let chip8 = server.join().expect("server thread");
+    assert_eq!(chip8.cpu().V[0], 0x2A);
+    assert_eq!(chip8.memory().RAM[0x300..0x303], [0xAB, 0xCD, 0xEF]);
+}
+
+#[test]
+fn bad_checksums_are_nacked_and_acks_can_be_turned_off() {
+    let (mut client, server) = start_server();
+    client.send_raw(b"$g#00");
+    assert_eq!(client.byte(), b'-');
+    /* Acks from the client and line noise between packets are skipped */
+    client.send_raw(b"++");
+    assert_eq!(client.request("QStartNoAckMode"), "OK");
+    client.send("m200,2");
+    assert_eq!(client.reply(), "6005");
+    client.send("D");
+    assert_eq!(client.reply(), "OK");
+    server.join().expect("server thread");
+}
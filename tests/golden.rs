@@ -0,0 +1,166 @@
+/*
+    Golden-frame regression tests: every ROM in assets/roms runs headless with a
+    fixed seed and its final screen is compared to tests/golden/<rom>.txt.
+
+    After an intentional behaviour change, regenerate the golden files with
+        UPDATE_GOLDEN=1 cargo test --test golden
+    and review the diff. The platform, frames and seed lines at the top of a
+    golden file are kept when regenerating, edit them to change how a ROM runs.
+*/
+use chip8::CHIP8;
+use chip8::emulator::{Platform, Rng};
+use chip8::runner::Runner;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const ROM_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/roms");
+const GOLDEN_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+/* Set to regenerate the golden files instead of checking them */
+const UPDATE_ENV: &str = "UPDATE_GOLDEN";
+/* Settings of ROMs without a golden file yet */
+const DEFAULT_FRAMES: usize = 300;
+const DEFAULT_SEED: u64 = 1;
+
+/* Run settings and expected result of one ROM */
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Golden {
+    platform: Platform,
+    frames: usize,
+    seed: u64,
+    outcome: String,
+    hash: u64,
+    screen: String,
+}
+impl Golden {
+    /* Settings of a ROM run for the first time */
+    fn new() -> Golden {
+        Golden {
+            platform: Platform::Chip8,
+            frames: DEFAULT_FRAMES,
+            seed: DEFAULT_SEED,
+            outcome: String::new(),
+            hash: 0,
+            screen: String::new(),
+        }
+    }
+    fn parse(text: &str) -> Result<Golden, String> {
+        let (header, screen) = text.split_once("screen\n").ok_or("missing 'screen' line")?;
+        let mut golden = Golden {
+            screen: screen.to_string(),
+            ..Golden::new()
+        };
+        for line in header.lines() {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            match key {
+                "platform" => golden.platform = value.parse()?,
+                "frames" => golden.frames = value.parse().map_err(|e| format!("frames: {e}"))?,
+                "seed" => golden.seed = value.parse().map_err(|e| format!("seed: {e}"))?,
+                "outcome" => golden.outcome = value.to_string(),
+                "hash" => {
+                    golden.hash =
+                        u64::from_str_radix(value, 16).map_err(|e| format!("hash: {e}"))?
+                }
+                _ => return Err(format!("unknown line '{line}'")),
+            }
+        }
+        Ok(golden)
+    }
+    /* Runs `rom` with the settings of this golden file, returning what it should contain now */
+    fn rerun(&self, rom: &Path) -> Golden {
+        let mut chip8 = CHIP8::with_platform(self.platform);
+        chip8.set_rng(Rng::from_seed(self.seed));
+        chip8.set_rewind_frames(0);
+        chip8
+            .load(rom)
+            .unwrap_or_else(|e| panic!("{}: {e}", rom.display()));
+        let mut runner = Runner::new(chip8, self.frames);
+        let outcome = runner.run();
+        let report = runner.report(&outcome);
+        Golden {
+            outcome: report.outcome,
+            hash: report.frame_hash,
+            screen: report.screen.to_ascii(),
+            ..self.clone()
+        }
+    }
+}
+impl fmt::Display for Golden {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "platform {}", self.platform)?;
+        writeln!(f, "frames {}", self.frames)?;
+        writeln!(f, "seed {}", self.seed)?;
+        writeln!(f, "outcome {}", self.outcome)?;
+        writeln!(f, "hash {:016x}", self.hash)?;
+        writeln!(f, "screen")?;
+        write!(f, "{}", self.screen)
+    }
+}
+
+fn roms() -> Vec<PathBuf> {
+    let mut roms: Vec<PathBuf> = fs::read_dir(ROM_DIR)
+        .expect("assets/roms is readable")
+        .map(|entry| entry.expect("ROM directory entry").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ch8"))
+        .collect();
+    roms.sort();
+    roms
+}
+
+fn golden_path(rom: &Path) -> PathBuf {
+    let stem = rom.file_stem().expect("ROM file name").to_string_lossy();
+    Path::new(GOLDEN_DIR).join(format!("{stem}.txt"))
+}
+
+#[test]
+fn bundled_roms_match_golden_frames() {
+    let update = std::env::var_os(UPDATE_ENV).is_some();
+    let mut failures = Vec::new();
+    for rom in roms() {
+        let path = golden_path(&rom);
+        let name = rom.file_name().unwrap_or_default().to_string_lossy();
+        let expected = match fs::read_to_string(&path) {
+            Ok(text) => match Golden::parse(&text) {
+                Ok(golden) => Some(golden),
+                Err(e) => panic!("{}: {e}", path.display()),
+            },
+            Err(_) => None,
+        };
+        let settings = expected.clone().unwrap_or_else(Golden::new);
+        let actual = settings.rerun(&rom);
+        if update {
+            if expected.as_ref() != Some(&actual) {
+                fs::write(&path, actual.to_string()).expect("golden file is writable");
+                eprintln!("Updated {}", path.display());
+            }
+            continue;
+        }
+        match expected {
+            None => failures.push(format!("{name}: no golden file at {}", path.display())),
+            Some(expected) if expected != actual => {
+                failures.push(format!("{name}: expected\n{expected}\ngot\n{actual}"))
+            }
+            Some(_) => {}
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{} ROM(s) differ from their golden frames, run `{UPDATE_ENV}=1 cargo test --test golden` \
+         if the change is intended:\n\n{}",
+        failures.len(),
+        failures.join("\n")
+    );
+}
+
+/* Golden files of removed or renamed ROMs would silently stop being checked */
+#[test]
+fn every_golden_file_has_a_rom() {
+    let roms: Vec<PathBuf> = roms().iter().map(|rom| golden_path(rom)).collect();
+    let stale: Vec<String> = fs::read_dir(GOLDEN_DIR)
+        .expect("tests/golden is readable")
+        .map(|entry| entry.expect("golden directory entry").path())
+        .filter(|path| !roms.contains(path))
+        .map(|path| path.display().to_string())
+        .collect();
+    assert!(stale.is_empty(), "golden files without a ROM: {stale:?}");
+}
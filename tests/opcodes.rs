@@ -0,0 +1,654 @@
+/*
+    Per-opcode tests: every case in the JSON files of tests/opcodes sets up the machine,
+    executes a single instruction and compares registers, stack, RAM and screen
+    with the expected state. A case runs on every platform it applies to, so
+    quirk dependent behaviour is checked under each quirk profile.
+
+    A case file is a JSON array of cases:
+        {
+            "name": "8XY4 sets VF on carry",
+            "opcode": "0x8014",
+            "initial": { "v": { "V0": "0xFF", "V1": 1 } },
+            "final": { "v": { "V0": 0, "VF": 1 } }
+        }
+
+    Case keys:
+        name        shown when the case fails
+        opcode      written at the initial PC, or left out when "ram" holds it
+        platforms   only run on these platforms, instead of every platform
+                    whose instruction set has the opcode
+        quirks      only run on platforms with these quirk values, e.g. {"shift": true}
+        on_error    error policy, "halt" (default), "wrap" or "ignore"
+        fault       the instruction must fail, "final" is ignored
+        initial     state before the instruction
+        final       expected state after it, only what changes has to be listed
+
+    State keys, numbers are JSON numbers or "0x" prefixed hex strings:
+        pc          defaults to 0x200, and to the initial PC + 2 in "final"
+        i, dt, st   registers, 0 unless given
+        v           16 values, or an object of some registers, e.g. {"V0": 1, "VF": 0}
+        stack       return addresses, oldest first
+        ram         [[address, byte or [bytes]], ...], "final" checks only these addresses
+                    and the initial ones
+        pixels      [[x, y], ...] lit pixels, "final" checks the whole screen is exactly these
+        hires       high resolution mode (SUPER-CHIP)
+        planes      selected drawing planes (XO-CHIP), 1 unless given
+        rpl         RPL user flags from flag 0 on (SUPER-CHIP)
+        pattern     16 byte audio pattern and "pitch" (XO-CHIP)
+        keys        keys held down, initial only
+        rng         CXNN generator, "xorshift" or "vip-like", and "seed" (default 0), initial only
+        exited      00FD was executed, final only
+
+    Cases in the same format from elsewhere, such as imported test vectors, are
+    run too when their files or directories are listed in CHIP8_OPCODE_CASES,
+    separated like PATH.
+*/
+use chip8::CHIP8;
+use chip8::emulator::cpu::{AUDIO_PATTERN_BYTES, DEFAULT_PITCH};
+use chip8::emulator::display::PLANE_1;
+use chip8::emulator::{
+    ErrorPolicy, InstructionSet, Mnemonics, Platform, Quirks, Rng, RngAlgorithm, decode,
+};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::mem::{Discriminant, discriminant};
+use std::path::{Path, PathBuf};
+
+const CASE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/opcodes");
+/* Extra case files or directories */
+const EXTRA_CASES_ENV: &str = "CHIP8_OPCODE_CASES";
+const START_PC: u16 = 0x200;
+const NUM_V_REGS: usize = 16;
+const QUIRK_NAMES: [&str; 6] = [
+    "shift",
+    "load_store_increment",
+    "jump_with_vx",
+    "vf_reset",
+    "clipping",
+    "display_wait",
+];
+
+/* Machine state a case starts from, or the part of it a case expects */
+#[derive(Debug, Clone, Default)]
+struct State {
+    pc: Option<u16>,
+    i: Option<u16>,
+    v: [Option<u8>; NUM_V_REGS],
+    dt: Option<u8>,
+    st: Option<u8>,
+    stack: Option<Vec<u16>>,
+    ram: Vec<(usize, u8)>,
+    pixels: Option<BTreeSet<(usize, usize)>>,
+    hires: Option<bool>,
+    planes: Option<u8>,
+    rpl: Option<Vec<u8>>,
+    pattern: Option<[u8; AUDIO_PATTERN_BYTES]>,
+    pitch: Option<u8>,
+    keys: Vec<usize>,
+    rng: Option<RngAlgorithm>,
+    seed: u64,
+    exited: Option<bool>,
+}
+impl State {
+    fn parse(value: &Value) -> Result<State, String> {
+        let mut state = State::default();
+        for (key, value) in object(value)? {
+            let field = |e: String| format!("{key}: {e}");
+            match key.as_str() {
+                "pc" => state.pc = Some(narrow(value).map_err(field)?),
+                "i" => state.i = Some(narrow(value).map_err(field)?),
+                "dt" => state.dt = Some(narrow(value).map_err(field)?),
+                "st" => state.st = Some(narrow(value).map_err(field)?),
+                "v" => state.v = registers(value).map_err(field)?,
+                "stack" => {
+                    let stack: Result<Vec<u16>, _> = array(value)?.iter().map(narrow).collect();
+                    state.stack = Some(stack.map_err(field)?);
+                }
+                "ram" => state.ram = ram(value).map_err(field)?,
+                "pixels" => state.pixels = Some(pixels(value).map_err(field)?),
+                "hires" => state.hires = Some(boolean(value).map_err(field)?),
+                "planes" => state.planes = Some(narrow(value).map_err(field)?),
+                "rpl" => {
+                    let rpl: Result<Vec<u8>, _> = array(value)?.iter().map(narrow).collect();
+                    state.rpl = Some(rpl.map_err(field)?);
+                }
+                "pattern" => {
+                    let pattern: Vec<u8> = array(value)?
+                        .iter()
+                        .map(narrow)
+                        .collect::<Result<_, _>>()
+                        .map_err(field)?;
+                    let pattern = pattern
+                        .try_into()
+                        .map_err(|_| format!("pattern: expected {AUDIO_PATTERN_BYTES} bytes"))?;
+                    state.pattern = Some(pattern);
+                }
+                "pitch" => state.pitch = Some(narrow(value).map_err(field)?),
+                "keys" => {
+                    let keys: Result<Vec<usize>, _> = array(value)?.iter().map(narrow).collect();
+                    state.keys = keys.map_err(field)?;
+                }
+                "rng" => {
+                    let name = value.as_str().ok_or("rng: expected a string")?;
+                    state.rng = Some(name.parse().map_err(field)?);
+                }
+                "seed" => state.seed = number(value).map_err(field)?,
+                "exited" => state.exited = Some(boolean(value).map_err(field)?),
+                _ => return Err(format!("unknown state key '{key}'")),
+            }
+        }
+        Ok(state)
+    }
+    #[inline]
+    fn pc(&self) -> u16 {
+        self.pc.unwrap_or(START_PC)
+    }
+}
+
+/* One instruction to execute, with the state before and after it */
+#[derive(Debug, Clone)]
+struct Case {
+    name: String,
+    opcode: Option<u16>,
+    platforms: Option<Vec<Platform>>,
+    quirks: Vec<(String, bool)>,
+    error_policy: ErrorPolicy,
+    fault: bool,
+    initial: State,
+    expected: State,
+}
+impl Case {
+    fn parse(value: &Value) -> Result<Case, String> {
+        let fields = object(value)?;
+        let name = match fields.get("name") {
+            Some(Value::String(name)) => name.clone(),
+            _ => return Err("missing 'name'".to_string()),
+        };
+        let parse = || -> Result<Case, String> {
+            let mut case = Case {
+                name: name.clone(),
+                opcode: None,
+                platforms: None,
+                quirks: Vec::new(),
+                error_policy: ErrorPolicy::Halt,
+                fault: false,
+                initial: State::default(),
+                expected: State::default(),
+            };
+            for (key, value) in fields {
+                match key.as_str() {
+                    "name" => {}
+                    "opcode" => case.opcode = Some(narrow(value)?),
+                    "platforms" => {
+                        let platforms: Result<Vec<Platform>, _> = array(value)?
+                            .iter()
+                            .map(|name| -> Result<Platform, String> {
+                                name.as_str().ok_or("expected a platform name")?.parse()
+                            })
+                            .collect();
+                        case.platforms = Some(platforms?);
+                    }
+                    "quirks" => {
+                        for (quirk, value) in object(value)? {
+                            if !QUIRK_NAMES.contains(&quirk.as_str()) {
+                                return Err(format!("unknown quirk '{quirk}'"));
+                            }
+                            case.quirks.push((quirk.clone(), boolean(value)?));
+                        }
+                    }
+                    "on_error" => {
+                        case.error_policy = value
+                            .as_str()
+                            .ok_or("expected an error policy name")?
+                            .parse()?
+                    }
+                    "fault" => case.fault = boolean(value)?,
+                    "initial" => case.initial = State::parse(value)?,
+                    "final" => case.expected = State::parse(value)?,
+                    _ => return Err(format!("unknown case key '{key}'")),
+                }
+            }
+            if case.opcode().is_none() {
+                return Err("no 'opcode' and no RAM at the initial PC".to_string());
+            }
+            Ok(case)
+        };
+        parse().map_err(|e| format!("{name}: {e}"))
+    }
+    /* The instruction, given directly or found in the initial RAM */
+    fn opcode(&self) -> Option<u16> {
+        if self.opcode.is_some() {
+            return self.opcode;
+        }
+        let pc = self.initial.pc() as usize;
+        let byte = |addr| {
+            self.initial
+                .ram
+                .iter()
+                .rev()
+                .find(|&&(at, _)| at == addr)
+                .map(|&(_, value)| value)
+        };
+        Some(u16::from_be_bytes([byte(pc)?, byte(pc + 1)?]))
+    }
+    fn applies_to(&self, platform: Platform) -> bool {
+        let quirks = platform.quirks();
+        let quirks_match = self
+            .quirks
+            .iter()
+            .all(|(name, value)| quirk(&quirks, name) == *value);
+        let platform_match = match &self.platforms {
+            Some(platforms) => platforms.contains(&platform),
+            None => self
+                .opcode()
+                .is_some_and(|opcode| instruction_set(opcode) <= platform.instruction_set()),
+        };
+        quirks_match && platform_match
+    }
+    /* Executes the instruction on `platform`, returning the differences from the expected state */
+    fn run(&self, platform: Platform) -> Vec<String> {
+        let mut chip8 = CHIP8::with_platform(platform);
+        chip8.set_error_policy(self.error_policy);
+        chip8.set_rng(Rng::with_algorithm(
+            self.initial.rng.unwrap_or_default(),
+            self.initial.seed,
+        ));
+        let initial = &self.initial;
+        let pc = initial.pc();
+        for &(addr, value) in &initial.ram {
+            if let Err(e) = chip8.memory_mut().write(addr, value) {
+                return vec![format!("initial RAM: {e}")];
+            }
+        }
+        if let Some(opcode) = self.opcode {
+            let [high, low] = opcode.to_be_bytes();
+            let memory = chip8.memory_mut();
+            if let Err(e) = memory
+                .write(pc as usize, high)
+                .and_then(|_| memory.write(pc as usize + 1, low))
+            {
+                return vec![format!("opcode at {pc:#06x}: {e}")];
+            }
+        }
+        let stack = initial.stack.clone().unwrap_or_default();
+        let cpu = chip8.cpu_mut();
+        cpu.PROGRAM_COUNTER = pc;
+        cpu.I = initial.i.unwrap_or(0);
+        cpu.D_TIMER = initial.dt.unwrap_or(0);
+        cpu.S_TIMER = initial.st.unwrap_or(0);
+        for (reg, value) in cpu.V.iter_mut().zip(initial.v) {
+            *reg = value.unwrap_or(0);
+        }
+        cpu.STACK_POINTER = stack.len() as u8;
+        if let Some(rpl) = &initial.rpl {
+            cpu.RPL[..rpl.len()].copy_from_slice(rpl);
+        }
+        cpu.AUDIO_PATTERN = initial.pattern;
+        cpu.PITCH = initial.pitch.unwrap_or(DEFAULT_PITCH);
+        chip8.memory_mut().STACK = stack;
+        let display = chip8.display_mut();
+        display.set_hires(initial.hires.unwrap_or(false));
+        display.select_planes(initial.planes.unwrap_or(PLANE_1));
+        for &(x, y) in initial.pixels.iter().flatten() {
+            display.toggle_pixel(x, y, display.selected_planes());
+        }
+        for &key in &initial.keys {
+            chip8.keypad_mut().set_key(key, true);
+        }
+
+        match chip8.step() {
+            Err(_) if self.fault => Vec::new(),
+            Ok(_) if self.fault => vec!["expected a fault".to_string()],
+            Err(e) => vec![e.to_string()],
+            Ok(_) => self.compare(&chip8),
+        }
+    }
+    fn compare(&self, chip8: &CHIP8) -> Vec<String> {
+        let (initial, expected) = (&self.initial, &self.expected);
+        let mut diffs = Vec::new();
+        let cpu = chip8.cpu();
+        let pc = expected.pc.unwrap_or(initial.pc().wrapping_add(2));
+        check(
+            &mut diffs,
+            "PC".into(),
+            cpu.PROGRAM_COUNTER.into(),
+            pc.into(),
+        );
+        let i = expected.i.or(initial.i).unwrap_or(0);
+        check(&mut diffs, "I".into(), cpu.I.into(), i.into());
+        let dt = expected.dt.or(initial.dt).unwrap_or(0);
+        check(&mut diffs, "DT".into(), cpu.D_TIMER.into(), dt.into());
+        let st = expected.st.or(initial.st).unwrap_or(0);
+        check(&mut diffs, "ST".into(), cpu.S_TIMER.into(), st.into());
+        for reg in 0..NUM_V_REGS {
+            let value = expected.v[reg].or(initial.v[reg]).unwrap_or(0);
+            check(
+                &mut diffs,
+                format!("V{reg:X}"),
+                cpu.V[reg].into(),
+                value.into(),
+            );
+        }
+        /* Bytes given in the final state win over the initial ones */
+        let memory = chip8.memory();
+        let ram: BTreeMap<usize, u8> = initial.ram.iter().chain(&expected.ram).copied().collect();
+        for (addr, value) in ram {
+            let actual = memory.RAM.get(addr).copied().unwrap_or(0);
+            check(
+                &mut diffs,
+                format!("RAM[{addr:#06x}]"),
+                actual.into(),
+                value.into(),
+            );
+        }
+
+        let stack = expected
+            .stack
+            .as_ref()
+            .or(initial.stack.as_ref())
+            .cloned()
+            .unwrap_or_default();
+        if memory.STACK != stack {
+            diffs.push(format!(
+                "stack = {:04x?}, expected {stack:04x?}",
+                memory.STACK
+            ));
+        }
+        if cpu.STACK_POINTER as usize != memory.STACK.len() {
+            diffs.push(format!(
+                "SP = {}, stack holds {}",
+                cpu.STACK_POINTER,
+                memory.STACK.len()
+            ));
+        }
+        let pitch = expected.pitch.or(initial.pitch).unwrap_or(DEFAULT_PITCH);
+        check(&mut diffs, "pitch".into(), cpu.PITCH.into(), pitch.into());
+        let pattern = expected.pattern.or(initial.pattern);
+        if cpu.AUDIO_PATTERN != pattern {
+            diffs.push(format!(
+                "pattern = {:02x?}, expected {pattern:02x?}",
+                cpu.AUDIO_PATTERN
+            ));
+        }
+        let rpl = expected.rpl.as_ref().or(initial.rpl.as_ref());
+        for (flag, &value) in rpl.into_iter().flatten().enumerate() {
+            check(
+                &mut diffs,
+                format!("RPL{flag}"),
+                cpu.RPL[flag].into(),
+                value.into(),
+            );
+        }
+        let planes = expected.planes.or(initial.planes).unwrap_or(PLANE_1);
+        let selected = chip8.display().selected_planes();
+        check(&mut diffs, "planes".into(), selected.into(), planes.into());
+        let hires = expected.hires.or(initial.hires).unwrap_or(false);
+        if chip8.display().is_hires() != hires {
+            diffs.push(format!("hires = {}, expected {hires}", !hires));
+        }
+        let pixels = expected
+            .pixels
+            .as_ref()
+            .or(initial.pixels.as_ref())
+            .cloned()
+            .unwrap_or_default();
+        let lit = lit_pixels(chip8);
+        if lit != pixels {
+            diffs.push(format!("pixels = {lit:?}, expected {pixels:?}"));
+        }
+        let exited = expected.exited.unwrap_or(false);
+        if chip8.has_exited() != exited {
+            diffs.push(format!("exited = {}, expected {exited}", !exited));
+        }
+        diffs
+    }
+}
+
+fn check(diffs: &mut Vec<String>, what: String, actual: u64, expected: u64) {
+    if actual != expected {
+        diffs.push(format!("{what} = {actual:#x}, expected {expected:#x}"));
+    }
+}
+
+/* Lit pixels in the screen coordinates of the current mode */
+fn lit_pixels(chip8: &CHIP8) -> BTreeSet<(usize, usize)> {
+    let display = chip8.display();
+    let scale = display.buffer_width() / display.width();
+    let mut lit = BTreeSet::new();
+    for y in 0..display.height() {
+        for x in 0..display.width() {
+            if display.is_pixel_on(y * scale * display.buffer_width() + x * scale) {
+                lit.insert((x, y));
+            }
+        }
+    }
+    lit
+}
+
+fn quirk(quirks: &Quirks, name: &str) -> bool {
+    match name {
+        "shift" => quirks.shift,
+        "load_store_increment" => quirks.load_store_increment,
+        "jump_with_vx" => quirks.jump_with_vx,
+        "vf_reset" => quirks.vf_reset,
+        "clipping" => quirks.clipping,
+        "display_wait" => quirks.display_wait,
+        _ => unreachable!("quirk names are checked when parsing"),
+    }
+}
+
+#[inline]
+fn instruction_set(opcode: u16) -> InstructionSet {
+    decode(opcode).instruction_set()
+}
+
+fn object(value: &Value) -> Result<&Map<String, Value>, String> {
+    value
+        .as_object()
+        .ok_or_else(|| format!("expected an object, found {value}"))
+}
+
+fn array(value: &Value) -> Result<&Vec<Value>, String> {
+    value
+        .as_array()
+        .ok_or_else(|| format!("expected an array, found {value}"))
+}
+
+fn boolean(value: &Value) -> Result<bool, String> {
+    value
+        .as_bool()
+        .ok_or_else(|| format!("expected true or false, found {value}"))
+}
+
+/* A JSON number, or a decimal or "0x" prefixed hexadecimal string */
+fn number(value: &Value) -> Result<u64, String> {
+    match value {
+        Value::Number(n) => n
+            .as_u64()
+            .ok_or_else(|| format!("{n} is not an unsigned integer")),
+        Value::String(text) => match text.strip_prefix("0x").or(text.strip_prefix("0X")) {
+            Some(hex) => u64::from_str_radix(hex, 16),
+            None => text.parse(),
+        }
+        .map_err(|e| format!("'{text}': {e}")),
+        _ => Err(format!("expected a number, found {value}")),
+    }
+}
+
+fn narrow<T: TryFrom<u64>>(value: &Value) -> Result<T, String> {
+    let n = number(value)?;
+    T::try_from(n).map_err(|_| format!("{n:#x} is out of range"))
+}
+
+/* All 16 registers as an array, or some of them by name */
+fn registers(value: &Value) -> Result<[Option<u8>; NUM_V_REGS], String> {
+    let mut v = [None; NUM_V_REGS];
+    match value {
+        Value::Array(values) if values.len() == NUM_V_REGS => {
+            for (reg, value) in v.iter_mut().zip(values) {
+                *reg = Some(narrow(value)?);
+            }
+        }
+        Value::Object(fields) => {
+            for (name, value) in fields {
+                let reg = name
+                    .strip_prefix(['V', 'v'])
+                    .filter(|digit| digit.len() == 1)
+                    .and_then(|digit| usize::from_str_radix(digit, 16).ok())
+                    .ok_or_else(|| format!("unknown register '{name}'"))?;
+                v[reg] = Some(narrow(value)?);
+            }
+        }
+        _ => {
+            return Err(format!(
+                "expected 16 values or registers by name, found {value}"
+            ));
+        }
+    }
+    Ok(v)
+}
+
+/* [[address, byte or [bytes]], ...], bytes of a list go to consecutive addresses */
+fn ram(value: &Value) -> Result<Vec<(usize, u8)>, String> {
+    let mut bytes = Vec::new();
+    for entry in array(value)? {
+        let [addr, value] = array(entry)?.as_slice() else {
+            return Err(format!("expected [address, value], found {entry}"));
+        };
+        let addr: usize = narrow(addr)?;
+        match value {
+            Value::Array(values) => {
+                for (offset, value) in values.iter().enumerate() {
+                    bytes.push((addr + offset, narrow(value)?));
+                }
+            }
+            value => bytes.push((addr, narrow(value)?)),
+        }
+    }
+    Ok(bytes)
+}
+
+fn pixels(value: &Value) -> Result<BTreeSet<(usize, usize)>, String> {
+    array(value)?
+        .iter()
+        .map(|pixel| match array(pixel)?.as_slice() {
+            [x, y] => Ok((narrow(x)?, narrow(y)?)),
+            _ => Err(format!("expected [x, y], found {pixel}")),
+        })
+        .collect()
+}
+
+/* JSON files of a directory, or the file itself */
+fn json_files(path: &Path) -> Vec<PathBuf> {
+    if !path.is_dir() {
+        return vec![path.to_path_buf()];
+    }
+    let mut files: Vec<PathBuf> = fs::read_dir(path)
+        .unwrap_or_else(|e| panic!("{}: {e}", path.display()))
+        .map(|entry| entry.expect("case directory entry").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+    files
+}
+
+fn load_cases(path: &Path) -> Vec<Case> {
+    let text = fs::read_to_string(path).unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+    let json: Value =
+        serde_json::from_str(&text).unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+    array(&json)
+        .and_then(|cases| cases.iter().map(Case::parse).collect())
+        .unwrap_or_else(|e| panic!("{}: {e}", path.display()))
+}
+
+fn bundled_cases() -> Vec<(PathBuf, Vec<Case>)> {
+    json_files(Path::new(CASE_DIR))
+        .into_iter()
+        .map(|path| {
+            let cases = load_cases(&path);
+            (path, cases)
+        })
+        .collect()
+}
+
+fn extra_cases() -> Vec<(PathBuf, Vec<Case>)> {
+    let Some(paths) = std::env::var_os(EXTRA_CASES_ENV) else {
+        return Vec::new();
+    };
+    std::env::split_paths(&paths)
+        .filter(|path| !path.as_os_str().is_empty())
+        .flat_map(|path| json_files(&path))
+        .map(|path| {
+            let cases = load_cases(&path);
+            (path, cases)
+        })
+        .collect()
+}
+
+#[test]
+fn opcode_cases_pass_on_every_platform() {
+    let mut runs = 0;
+    let mut failures = Vec::new();
+    for (path, cases) in bundled_cases().into_iter().chain(extra_cases()) {
+        let file = path.file_name().unwrap_or_default().to_string_lossy();
+        for case in &cases {
+            for platform in Platform::ALL.into_iter().filter(|&p| case.applies_to(p)) {
+                runs += 1;
+                let diffs = case.run(platform);
+                if !diffs.is_empty() {
+                    failures.push(format!(
+                        "{file}: {} [{platform}]: {}",
+                        case.name,
+                        diffs.join(", ")
+                    ));
+                }
+            }
+        }
+    }
+    assert!(runs > 0, "no opcode cases found in {CASE_DIR}");
+    assert!(
+        failures.is_empty(),
+        "{} of {runs} opcode case runs failed:\n{}",
+        failures.len(),
+        failures.join("\n")
+    );
+}
+
+/* A case whose filters match no platform would silently never run */
+#[test]
+fn every_opcode_case_runs_somewhere() {
+    let idle: Vec<String> = bundled_cases()
+        .iter()
+        .flat_map(|(_, cases)| cases)
+        .filter(|case| !Platform::ALL.into_iter().any(|p| case.applies_to(p)))
+        .map(|case| case.name.clone())
+        .collect();
+    assert!(idle.is_empty(), "cases matching no platform: {idle:?}");
+}
+
+/* Every instruction the decoder knows has at least one case that executes it */
+#[test]
+fn opcode_cases_cover_every_instruction() {
+    let covered: HashSet<Discriminant<Mnemonics>> = bundled_cases()
+        .iter()
+        .flat_map(|(_, cases)| cases)
+        .filter(|case| !case.fault)
+        .filter_map(Case::opcode)
+        .map(|opcode| discriminant(&decode(opcode)))
+        .collect();
+    let mut seen = HashSet::new();
+    let mut missing = Vec::new();
+    for opcode in 0..=u16::MAX {
+        let Ok(mnemonic) = Mnemonics::try_from(opcode) else {
+            continue;
+        };
+        if seen.insert(discriminant(&mnemonic)) && !covered.contains(&discriminant(&mnemonic)) {
+            missing.push(format!("{opcode:04X} ({mnemonic})"));
+        }
+    }
+    assert!(
+        missing.is_empty(),
+        "instructions without a case: {missing:?}"
+    );
+}
@@ -0,0 +1,43 @@
+/*
+    Runs the chip8-run binary and checks that the JSON report's success
+    agrees with its exit status.
+*/
+use serde_json::Value;
+use std::fs;
+use std::process::Command;
+
+/* 0x200: V0 = 5, 0x202: jump to itself */
+const PROGRAM: [u8; 4] = [0x60, 0x05, 0x12, 0x02];
+/* 0x200: an opcode no platform decodes */
+const FAULT: [u8; 2] = [0xFF, 0xFF];
+
+/* Runs a ROM written to the temporary directory, returns the exit status and the report */
+fn run(name: &str, rom: &[u8], args: &[&str]) -> (i32, Value) {
+    let path = std::env::temp_dir().join(format!("chip8-run-{}-{name}.ch8", std::process::id()));
+    fs::write(&path, rom).expect("write ROM");
+    let output = Command::new(env!("CARGO_BIN_EXE_chip8-run"))
+        .arg(&path)
+        .args(["--json", "--frames", "10"])
+        .args(args)
+        .output()
+        .expect("run chip8-run");
+    fs::remove_file(&path).ok();
+    let report = serde_json::from_slice(&output.stdout).expect("report is JSON");
+    (output.status.code().expect("exit status"), report)
+}
+
+#[test]
+fn report_success_agrees_with_the_exit_status() {
+    let cases: [(&str, &[u8], &[&str], i32); 5] = [
+        ("frames", &PROGRAM, &[], 0),
+        ("pc", &PROGRAM, &["--until-pc", "0x202"], 0),
+        ("timeout", &PROGRAM, &["--until-pc", "0x300"], 2),
+        ("hash", &PROGRAM, &["--expect-hash", "0x1"], 1),
+        ("fault", &FAULT, &[], 1),
+    ];
+    for (name, rom, args, expected) in cases {
+        let (status, report) = run(name, rom, args);
+        assert_eq!(status, expected, "{name}: {report}");
+        assert_eq!(report["success"], status == 0, "{name}: {report}");
+    }
+}
@@ -0,0 +1,149 @@
+/*
+    Reading test suite results: check marks and crosses drawn next to labels,
+    and the beep test's sound over the frames its key is held.
+*/
+use chip8::emulator::display::{HIRES_HEIGHT, HIRES_WIDTH, PLANE_1};
+use chip8::emulator::{Display, Platform};
+use chip8::runner::{Marker, SuiteTest, Verdict, find_markers};
+use std::fs;
+
+#[rustfmt::skip]
+const CHECK: [&str; 4] = [
+    ".....#",
+    "....#.",
+    "#..#..",
+    ".##...",
+];
+#[rustfmt::skip]
+const CROSS: [&str; 5] = [
+    "#...#",
+    ".#.#.",
+    "..#..",
+    ".#.#.",
+    "#...#",
+];
+/* "8XY4" in the suite's 3x5 font, whose X on its own would read as a cross */
+const LABEL: [&str; 5] = [
+    "### #.# #.# #.#",
+    "#.# #.# #.# #.#",
+    "### .#. .#. ###",
+    "#.# #.# .#. ..#",
+    "### #.# .#. ..#",
+];
+
+/* Draws ASCII art with its top left corner at (x, y), '#' is a lit pixel */
+fn draw(display: &mut Display, x: usize, y: usize, art: &[&str]) {
+    for (dy, row) in art.iter().enumerate() {
+        for (dx, pixel) in row.chars().enumerate() {
+            if pixel == '#' {
+                display.toggle_pixel(x + dx, y + dy, PLANE_1);
+            }
+        }
+    }
+}
+
+fn marker(
+    line: usize,
+    label: usize,
+    index: usize,
+    (x, y): (usize, usize),
+    verdict: Verdict,
+) -> Marker {
+    Marker {
+        line,
+        label,
+        index,
+        x,
+        y,
+        verdict,
+    }
+}
+
+#[test]
+fn markers_after_labels() {
+    let mut display = Display::new();
+    draw(&mut display, 0, 0, &LABEL);
+    draw(&mut display, 20, 1, &CHECK);
+    draw(&mut display, 0, 8, &LABEL);
+    draw(&mut display, 20, 8, &CROSS);
+    draw(&mut display, 28, 9, &CHECK);
+    draw(&mut display, 40, 8, &LABEL);
+    draw(&mut display, 0, 16, &CROSS);
+    draw(&mut display, 10, 16, &LABEL);
+    draw(&mut display, 0, 24, &LABEL);
+
+    assert_eq!(
+        find_markers(&display),
+        [
+            marker(1, 1, 1, (20, 1), Verdict::Pass),
+            marker(2, 1, 1, (20, 8), Verdict::Fail),
+            marker(2, 1, 2, (28, 9), Verdict::Pass),
+            marker(3, 0, 1, (0, 16), Verdict::Fail),
+        ]
+    );
+}
+
+#[test]
+fn markers_are_in_chip8_pixels_of_the_current_mode() {
+    /* Low resolution on a high resolution buffer draws 2x2 blocks */
+    let mut display = Display::with_resolution(HIRES_WIDTH, HIRES_HEIGHT);
+    draw(&mut display, 30, 20, &LABEL);
+    draw(&mut display, 50, 20, &CROSS);
+    assert_eq!(
+        find_markers(&display),
+        [marker(1, 1, 1, (50, 20), Verdict::Fail)]
+    );
+
+    display.set_hires(true);
+    draw(&mut display, 100, 50, &LABEL);
+    draw(&mut display, 120, 51, &CHECK);
+    assert_eq!(
+        find_markers(&display),
+        [marker(1, 1, 1, (120, 51), Verdict::Pass)]
+    );
+}
+
+#[test]
+fn labels_alone_have_no_markers() {
+    let mut display = Display::new();
+    draw(&mut display, 0, 0, &LABEL);
+    draw(&mut display, 20, 0, &LABEL);
+    assert!(find_markers(&display).is_empty());
+}
+
+/* Runs a beep test ROM written to the temporary directory */
+fn beep(name: &str, rom: &[u8]) -> Verdict {
+    let path = std::env::temp_dir().join(format!("chip8-suite-{}-{name}.ch8", std::process::id()));
+    fs::write(&path, rom).expect("write ROM");
+    let report = SuiteTest::Beep.run(&path, Platform::Chip8, None);
+    fs::remove_file(&path).ok();
+    let report = report.expect("run ROM");
+    assert_eq!(report.results.len(), 1);
+    report.results[0].verdict
+}
+
+/* V0 = B, V1 = 4, then loop: skip unless key V0 is down, sound timer = V1 */
+const BEEP_WHILE_HELD: [u8; 10] = [0x60, 0x0B, 0x61, 0x04, 0xE0, 0xA1, 0xF1, 0x18, 0x12, 0x04];
+
+#[test]
+fn beep_sounds_only_while_the_key_is_held() {
+    assert_eq!(beep("held", &BEEP_WHILE_HELD), Verdict::Pass);
+}
+
+#[test]
+fn beep_fails_when_silent_or_always_sounding() {
+    let mut silent = BEEP_WHILE_HELD;
+    /* Sound timer = V1 becomes delay timer = V1 */
+    silent[7] = 0x15;
+    assert_eq!(beep("silent", &silent), Verdict::Fail);
+
+    let mut always = BEEP_WHILE_HELD;
+    /* Skip unless key V0 is down becomes V0 = V0 */
+    always[4..6].copy_from_slice(&[0x80, 0x00]);
+    assert_eq!(beep("always", &always), Verdict::Fail);
+
+    let mut latched = BEEP_WHILE_HELD;
+    /* V1 = 0xFF, the sound runs on for seconds after the release */
+    latched[3] = 0xFF;
+    assert_eq!(beep("latched", &latched), Verdict::Fail);
+}